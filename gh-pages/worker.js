@@ -0,0 +1,9 @@
+// Runs the game of a WorkerSimulation off the main thread, see src/view/web/worker.rs.
+import init, {run_worker} from './game/game_of_life.js';
+
+// Messages posted by the page while the module loads are handed to the game once it is ready.
+const pending = [];
+self.onmessage = event => pending.push(event.data);
+
+await init();
+run_worker(pending);
@@ -0,0 +1,487 @@
+use game_of_life::render::camera::MAX_ZOOM;
+use game_of_life::{AnimationFormat, Board, ColorMode, ImageImportOptions, PatternFormat, Placement, Rule, SnapshotFormat, Theme, Topology};
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::view::game_controller::{MAX_UPDATE_INTERVAL, MIN_UPDATE_INTERVAL};
+
+pub const USAGE: &str = "\
+Usage: game_of_life [OPTIONS] [PATTERN]
+
+Runs Conway's Game of Life from the PATTERN file (plaintext, .cells, RLE, or a .png, .ppm or .pgm image),
+or from a random board.
+
+Options:
+      --width <PIXELS>         Width of the board in pixels, at most 100000 cells [default: 600]
+      --height <PIXELS>        Height of the board in pixels, at most 100000 cells [default: 500]
+      --cell-size <PIXELS>     Size of a cell in pixels, from 1 to 64 [default: 3]
+      --interval <MS>          Time between two generations in milliseconds, from 10 to 5000 [default: 300]
+      --probability <P>        Probability of a cell being alive on a random board [default: 0.1]
+      --seed <SEED>            Seed of the random board, to reproduce a run
+      --rule <RULE>            Life-like rule such as B36/S23 [default: the pattern's rule, or B3/S23]
+      --topology <TOPOLOGY>    bounded or torus [default: bounded]
+      --generation <N>         Generation to start at, computed before the board is shown [default: 0]
+      --position <X,Y>         Cell of the top-left corner of the pattern [default: centred]
+      --transform <TRANSFORM>  identity, rot90, rot180, rot270, flip-h, flip-v, flip-diag or flip-antidiag
+      --paste-mode <MODE>      or, xor or copy [default: or]
+      --view <VIEW>            desktop (a window) or terminal [default: desktop]
+      --theme <THEME>          light, dark or amber [default: light]
+      --color-mode <MODE>      plain, age (colours cells by age) or trail (fading dead cells) [default: plain]
+
+Image patterns:
+      --threshold <0-255>      Brightness below which a pixel is a live cell [default: 128]
+      --invert                 Make the bright pixels the live cells, for screenshots with a dark background
+      --pixels-per-cell <N>    Size of a cell in the image, or auto to detect the grid of a screenshot [default: 1]
+
+Headless mode, to run without opening a window:
+      --headless               Run the game without any view and write the final board
+      --generations <N>        Number of generations to run, the maximum with --until-stable [default: 100000]
+      --until-stable           Stop once the board is extinct, still or oscillating
+      --census                 Also print the objects left on the final board, such as 12 blocks, 5 blinkers
+      --output <FILE>          File to write the final board to [default: standard output]
+      --format <FORMAT>        rle, cells or txt [default: rle]
+      --animation <FILE>       Also write the run as an animated .gif or .png (APNG), drawn with --cell-size,
+                               --theme and --color-mode
+      --delay <MS>             Time each generation is shown in the animation [default: 100]
+      --snapshot <FILE>        Also write the final board as a .png or .svg image, drawn with --cell-size and --theme
+      --grid                   Draw lines between the cells of the snapshot
+      --crop                   Crop the animation to the cells that are alive at some point, and the snapshot to
+                               the final live cells
+
+Soup search, to hunt for rare objects in random 16x16 soups:
+      --soup-search <FILE>     Run soups until they settle and write the census of their ash, with the seeds of the
+                               soups holding rare objects, to FILE. An existing FILE is resumed, with its rule and seed
+      --soups <N>              Number of soups to search in total [default: 1000]
+      --threads <N>            Number of soups run at once [default: number of CPUs]
+
+  -h, --help                   Print this help
+";
+
+const OPTIONS: [&str; 26] = [
+    "--width", "--height", "--cell-size", "--interval", "--probability", "--seed", "--rule", "--topology",
+    "--generation", "--position", "--transform", "--paste-mode", "--view", "--theme", "--color-mode",
+    "--generations", "--output", "--format", "--animation", "--delay", "--snapshot", "--threshold", "--pixels-per-cell",
+    "--soup-search", "--soups", "--threads",
+];
+
+// The desktop view is the web view when built for wasm32.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ViewKind {
+    Desktop,
+    Terminal,
+}
+
+impl FromStr for ViewKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "desktop" => Ok(ViewKind::Desktop),
+            "terminal" => Ok(ViewKind::Terminal),
+            _ => Err(format!("Unknown view '{}', expected one of: desktop, terminal", s)),
+        }
+    }
+}
+
+impl fmt::Display for ViewKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ViewKind::Desktop => write!(f, "desktop"),
+            ViewKind::Terminal => write!(f, "terminal"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Options {
+    pub pattern_filename: Option<String>,
+    pub placement: Placement,
+    pub width: usize,
+    pub height: usize,
+    pub cell_size: usize,
+    pub update_interval_ms: usize,
+    pub initial_alive_probability: f64,
+    pub seed: Option<u64>,
+    pub rule: Option<Rule>,
+    pub topology: Topology,
+    pub start_generation: u64,
+    pub view: ViewKind,
+    pub theme: Theme,
+    pub color_mode: ColorMode,
+    pub image_import: ImageImportOptions,
+    pub headless: bool,
+    pub generations: Option<u64>,
+    pub until_stable: bool,
+    pub census: bool,
+    pub output_filename: Option<String>,
+    pub output_format: Option<PatternFormat>,
+    pub animation_filename: Option<String>,
+    pub animation_format: AnimationFormat,
+    pub animation_delay_ms: Option<u32>,
+    pub snapshot_filename: Option<String>,
+    pub snapshot_format: SnapshotFormat,
+    pub grid_lines: bool,
+    pub crop: bool,
+    pub soup_search_filename: Option<String>,
+    pub soups: Option<u64>,
+    pub threads: Option<usize>,
+    // Not an option of the command line: set for the boards of shared links, whose size is part of the game.
+    pub keep_board_size: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            pattern_filename: None,
+            placement: Placement::default(),
+            width: 600,
+            height: 500,
+            cell_size: 3,
+            update_interval_ms: 300,
+            initial_alive_probability: 0.1,
+            seed: None,
+            rule: None,
+            topology: Topology::Bounded,
+            start_generation: 0,
+            view: ViewKind::Desktop,
+            theme: Theme::Light,
+            color_mode: ColorMode::Plain,
+            image_import: ImageImportOptions::default(),
+            headless: false,
+            generations: None,
+            until_stable: false,
+            census: false,
+            output_filename: None,
+            output_format: None,
+            animation_filename: None,
+            animation_format: AnimationFormat::Gif,
+            animation_delay_ms: None,
+            snapshot_filename: None,
+            snapshot_format: SnapshotFormat::Png,
+            grid_lines: false,
+            crop: false,
+            soup_search_filename: None,
+            soups: None,
+            threads: None,
+            keep_board_size: false,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Command {
+    Run(Box<Options>),
+    Help,
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut options = Options::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with('-') {
+            if options.pattern_filename.is_some() {
+                return Err(format!("Unexpected argument '{}', only one pattern can be given", arg));
+            }
+            options.pattern_filename = Some(arg.clone());
+            continue;
+        }
+
+        let (option, inline_value) = match arg.split_once('=') {
+            Some((option, value)) => (option, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        match option {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--headless" => {
+                options.headless = true;
+                continue;
+            }
+            "--until-stable" => {
+                options.until_stable = true;
+                continue;
+            }
+            "--census" => {
+                options.census = true;
+                continue;
+            }
+            "--invert" => {
+                options.image_import.invert = true;
+                continue;
+            }
+            "--grid" => {
+                options.grid_lines = true;
+                continue;
+            }
+            "--crop" => {
+                options.crop = true;
+                continue;
+            }
+            _ => {}
+        }
+
+        let value = match inline_value.or_else(|| args.next().cloned()) {
+            Some(value) => value,
+            None if is_known_option(option) => return Err(format!("Missing value for option '{}'", option)),
+            None => return Err(format!("Unknown option '{}'", option)),
+        };
+        match option {
+            "--width" => options.width = parse_number(option, &value)?,
+            "--height" => options.height = parse_number(option, &value)?,
+            "--cell-size" => options.cell_size = parse_number(option, &value)?,
+            "--interval" => options.update_interval_ms = parse_number(option, &value)?,
+            "--probability" => options.initial_alive_probability = parse_number(option, &value)?,
+            "--seed" => options.seed = Some(parse_number(option, &value)?),
+            "--rule" => options.rule = Some(value.parse()?),
+            "--topology" => options.topology = value.parse()?,
+            "--generation" => options.start_generation = parse_number(option, &value)?,
+            "--position" => options.placement.position = Some(parse_position(&value)?),
+            "--transform" => options.placement.transform = value.parse()?,
+            "--paste-mode" => options.placement.paste_mode = value.parse()?,
+            "--view" => options.view = value.parse()?,
+            "--theme" => options.theme = value.parse()?,
+            "--color-mode" => options.color_mode = value.parse()?,
+            "--threshold" => options.image_import.threshold = parse_number(option, &value)?,
+            "--pixels-per-cell" => {
+                options.image_import.cell_size = match value.as_str() {
+                    "auto" => None,
+                    _ => Some(parse_number(option, &value)?),
+                }
+            }
+            "--generations" => options.generations = Some(parse_number(option, &value)?),
+            "--output" => options.output_filename = Some(value),
+            "--format" => options.output_format = Some(value.parse()?),
+            "--animation" => {
+                options.animation_format = AnimationFormat::from_path(&value)
+                    .ok_or_else(|| format!("Unknown animation format for '{}', expected a .gif, .png or .apng file", value))?;
+                options.animation_filename = Some(value);
+            }
+            "--delay" => options.animation_delay_ms = Some(parse_number(option, &value)?),
+            "--snapshot" => {
+                options.snapshot_format = SnapshotFormat::from_path(&value)
+                    .ok_or_else(|| format!("Unknown snapshot format for '{}', expected a .png or .svg file", value))?;
+                options.snapshot_filename = Some(value);
+            }
+            "--soup-search" => options.soup_search_filename = Some(value),
+            "--soups" => options.soups = Some(parse_number(option, &value)?),
+            "--threads" => options.threads = Some(parse_number(option, &value)?),
+            _ => return Err(format!("Unknown option '{}'", option)),
+        }
+    }
+
+    validate(&options)?;
+    Ok(Command::Run(Box::new(options)))
+}
+
+fn is_known_option(option: &str) -> bool {
+    OPTIONS.contains(&option)
+}
+
+// Checks the options that cannot be checked one by one, also for options built by the web page.
+pub fn validate(options: &Options) -> Result<(), String> {
+    if options.cell_size == 0 {
+        return Err("The cell size must be at least 1 pixel".to_string());
+    }
+    if options.width < options.cell_size || options.height < options.cell_size {
+        return Err(format!("The board must be at least one cell ({} pixels) wide and high", options.cell_size));
+    }
+    if options.cell_size > MAX_ZOOM as usize {
+        return Err(format!("The cell size must be at most {} pixels, got {}", MAX_ZOOM, options.cell_size));
+    }
+    Board::check_size(options.width / options.cell_size, options.height / options.cell_size)?;
+    if options.image_import.cell_size == Some(0) {
+        return Err("The image cells must be at least 1 pixel".to_string());
+    }
+    let (min_interval_ms, max_interval_ms) = (MIN_UPDATE_INTERVAL.as_millis() as usize, MAX_UPDATE_INTERVAL.as_millis() as usize);
+    if !(min_interval_ms..=max_interval_ms).contains(&options.update_interval_ms) {
+        return Err(format!("The interval must be between {} and {} milliseconds, got {}", min_interval_ms, max_interval_ms, options.update_interval_ms));
+    }
+    if !(0.0..=1.0).contains(&options.initial_alive_probability) {
+        return Err(format!("The probability must be between 0 and 1, got {}", options.initial_alive_probability));
+    }
+    if options.headless && options.generations.is_none() && !options.until_stable {
+        return Err("The headless mode needs --generations or --until-stable".to_string());
+    }
+    let has_headless_options = options.generations.is_some() || options.until_stable || options.census
+        || options.output_filename.is_some() || options.output_format.is_some() || options.animation_filename.is_some() || options.snapshot_filename.is_some();
+    if !options.headless && has_headless_options {
+        return Err("--generations, --until-stable, --census, --output, --format, --animation and --snapshot are only valid with --headless".to_string());
+    }
+    if options.soup_search_filename.is_some() && (options.headless || options.pattern_filename.is_some()) {
+        return Err("--soup-search cannot be combined with a pattern or --headless".to_string());
+    }
+    if options.soup_search_filename.is_none() && (options.soups.is_some() || options.threads.is_some()) {
+        return Err("--soups and --threads are only valid with --soup-search".to_string());
+    }
+    if options.threads == Some(0) {
+        return Err("The soup search needs at least 1 thread".to_string());
+    }
+    if options.animation_filename.is_none() && options.animation_delay_ms.is_some() {
+        return Err("--delay is only valid with --animation".to_string());
+    }
+    if options.snapshot_filename.is_none() && options.grid_lines {
+        return Err("--grid is only valid with --snapshot".to_string());
+    }
+    if options.animation_filename.is_none() && options.snapshot_filename.is_none() && options.crop {
+        return Err("--crop is only valid with --animation or --snapshot".to_string());
+    }
+    Ok(())
+}
+
+fn parse_number<T: FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value.trim().parse().map_err(|_| format!("Invalid value '{}' for option '{}'", value, option))
+}
+
+fn parse_position(value: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("Invalid position '{}', expected X,Y", value);
+    let (x, y) = value.split_once(',').ok_or_else(invalid)?;
+    let x = x.trim().parse().map_err(|_| invalid())?;
+    let y = y.trim().parse().map_err(|_| invalid())?;
+    Ok((x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life::Transform;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse_args(&args)
+    }
+
+    #[test]
+    fn test_defaults() {
+        assert_eq!(parse(&[]), Ok(Command::Run(Box::default())));
+    }
+
+    #[test]
+    fn test_help() {
+        assert_eq!(parse(&["--width", "100", "--help"]), Ok(Command::Help));
+        assert_eq!(parse(&["-h"]), Ok(Command::Help));
+    }
+
+    #[test]
+    fn test_all_options() {
+        let command = parse(&[
+            "glider.rle", "--width", "800", "--height=400", "--cell-size", "4", "--interval", "50",
+            "--probability", "0.3", "--seed", "7", "--rule", "B36/S23", "--topology", "torus",
+            "--generation", "100", "--position", "1,2", "--transform", "rot90", "--paste-mode", "xor",
+            "--view", "terminal", "--theme", "dark", "--color-mode", "trail",
+        ]).unwrap();
+        let Command::Run(options) = command else { panic!("Expected options") };
+        assert_eq!(options.pattern_filename.as_deref(), Some("glider.rle"));
+        assert_eq!((options.width, options.height, options.cell_size), (800, 400, 4));
+        assert_eq!(options.update_interval_ms, 50);
+        assert_eq!(options.initial_alive_probability, 0.3);
+        assert_eq!(options.seed, Some(7));
+        assert_eq!(options.rule, Some(Rule::new(&[3, 6], &[2, 3])));
+        assert_eq!(options.topology, Topology::Torus);
+        assert_eq!(options.start_generation, 100);
+        assert_eq!(options.placement.position, Some((1, 2)));
+        assert_eq!(options.placement.transform, Transform::Rotate90);
+        assert_eq!(options.view, ViewKind::Terminal);
+        assert_eq!((options.theme, options.color_mode), (Theme::Dark, ColorMode::Trail));
+    }
+
+    #[test]
+    fn test_interval_range() {
+        assert!(parse(&["--interval", "10"]).is_ok());
+        assert!(parse(&["--interval", "5000"]).is_ok());
+        assert_eq!(parse(&["--interval", "1"]).err().as_deref(), Some("The interval must be between 10 and 5000 milliseconds, got 1"));
+        assert!(parse(&["--interval", "5001"]).is_err());
+    }
+
+    #[test]
+    fn test_board_size_limits() {
+        assert!(parse(&["--width", "300000", "--height", "200", "--cell-size", "3"]).is_ok());
+        assert!(parse(&["--width", "100000", "--height", "100000", "--cell-size", "1"]).is_err());
+        assert!(parse(&["--width", "100001", "--height", "1", "--cell-size", "1"]).is_err());
+        assert_eq!(parse(&["--cell-size", "65"]).err().as_deref(), Some("The cell size must be at most 64 pixels, got 65"));
+    }
+
+    #[test]
+    fn test_image_options() {
+        let command = parse(&["shot.png", "--threshold", "90", "--invert", "--pixels-per-cell", "auto"]).unwrap();
+        let Command::Run(options) = command else { panic!("Expected options") };
+        assert_eq!(options.image_import, ImageImportOptions { threshold: 90, invert: true, cell_size: None });
+
+        let Command::Run(options) = parse(&["--pixels-per-cell", "4"]).unwrap() else { panic!("Expected options") };
+        assert_eq!(options.image_import.cell_size, Some(4));
+    }
+
+    #[test]
+    fn test_soup_search_options() {
+        let command = parse(&["--soup-search", "soups.txt", "--soups", "5000", "--threads", "4", "--seed", "9"]).unwrap();
+        let Command::Run(options) = command else { panic!("Expected options") };
+        assert_eq!(options.soup_search_filename.as_deref(), Some("soups.txt"));
+        assert_eq!((options.soups, options.threads, options.seed), (Some(5000), Some(4), Some(9)));
+
+        assert!(parse(&["--soups", "10"]).is_err());
+        assert!(parse(&["--soups", "1000"]).is_err());
+        assert!(parse(&["--soup-search", "soups.txt", "--threads", "0"]).is_err());
+        assert!(parse(&["--soup-search", "soups.txt", "--headless", "--generations", "10"]).is_err());
+    }
+
+    #[test]
+    fn test_headless_options() {
+        let command = parse(&["--headless", "--until-stable", "--generations", "500", "--output", "out.cells", "--format", "cells"]).unwrap();
+        let Command::Run(options) = command else { panic!("Expected options") };
+        assert!(options.headless && options.until_stable);
+        assert_eq!(options.generations, Some(500));
+        assert_eq!(options.output_filename.as_deref(), Some("out.cells"));
+        assert_eq!(options.output_format, Some(PatternFormat::Cells));
+
+        assert!(parse(&["--headless"]).is_err());
+        assert!(parse(&["--generations", "10"]).is_err());
+        assert!(parse(&["--census"]).is_err());
+        let Command::Run(options) = parse(&["--headless", "--until-stable", "--census"]).unwrap() else { panic!("Expected options") };
+        assert!(options.census);
+        assert!(parse(&["--headless", "--generations", "10", "--format", "png"]).is_err());
+        assert!(parse(&["--format", "rle"]).is_err());
+    }
+
+    #[test]
+    fn test_animation_options() {
+        let command = parse(&["--headless", "--generations", "50", "--animation", "run.png", "--delay", "40", "--crop"]).unwrap();
+        let Command::Run(options) = command else { panic!("Expected options") };
+        assert_eq!(options.animation_filename.as_deref(), Some("run.png"));
+        assert_eq!(options.animation_format, AnimationFormat::Apng);
+        assert_eq!((options.animation_delay_ms, options.crop), (Some(40), true));
+
+        assert!(parse(&["--headless", "--generations", "50", "--animation", "run.mp4"]).is_err());
+        assert!(parse(&["--animation", "run.gif"]).is_err());
+        assert!(parse(&["--headless", "--generations", "50", "--crop"]).is_err());
+        assert!(parse(&["--headless", "--generations", "50", "--delay", "100"]).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_options() {
+        let command = parse(&["--headless", "--generations", "5", "--snapshot", "board.svg", "--grid", "--crop"]).unwrap();
+        let Command::Run(options) = command else { panic!("Expected options") };
+        assert_eq!(options.snapshot_filename.as_deref(), Some("board.svg"));
+        assert_eq!(options.snapshot_format, SnapshotFormat::Svg);
+        assert!(options.grid_lines && options.crop);
+
+        assert!(parse(&["--headless", "--generations", "5", "--snapshot", "board.jpg"]).is_err());
+        assert!(parse(&["--snapshot", "board.png"]).is_err());
+        assert!(parse(&["--headless", "--generations", "5", "--animation", "run.gif", "--grid"]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        assert!(parse(&["--size", "3"]).unwrap_err().contains("Unknown option"));
+        assert!(parse(&["--width"]).unwrap_err().contains("Missing value"));
+        assert!(parse(&["--width", "wide"]).is_err());
+        assert!(parse(&["--cell-size", "0"]).is_err());
+        assert!(parse(&["--width", "2", "--cell-size", "3"]).is_err());
+        assert!(parse(&["--probability", "1.5"]).is_err());
+        assert!(parse(&["--topology", "sphere"]).is_err());
+        assert!(parse(&["--theme", "sepia"]).is_err());
+        assert!(parse(&["--threshold", "300"]).is_err());
+        assert!(parse(&["--pixels-per-cell", "0"]).is_err());
+        assert!(parse(&["--position", "3"]).is_err());
+        assert!(parse(&["one.rle", "two.rle"]).is_err());
+    }
+}
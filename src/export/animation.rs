@@ -0,0 +1,320 @@
+use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::iter;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::export::draw_region;
+use crate::game::board::Board;
+use crate::game::game_state::GameState;
+use crate::render::camera::MAX_ZOOM;
+use crate::render::frame::{Frame, Rgba};
+use crate::render::theme::{ColorMode, Palette, Theme};
+
+/// The file formats of an animation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AnimationFormat {
+    /// Animated GIF, readable everywhere but limited to 256 colours and 65535 pixels per side.
+    Gif,
+    /// Animated PNG, lossless and in full colour.
+    Apng,
+}
+
+impl AnimationFormat {
+    /// Guesses the format from the extension of a file name: `.gif`, or `.png` and `.apng`.
+    pub fn from_path(path: &str) -> Option<AnimationFormat> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gif" => Some(AnimationFormat::Gif),
+            "png" | "apng" => Some(AnimationFormat::Apng),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for AnimationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "gif" => Ok(AnimationFormat::Gif),
+            "apng" => Ok(AnimationFormat::Apng),
+            _ => Err(format!("Unknown animation format '{}', expected one of: gif, apng", s)),
+        }
+    }
+}
+
+impl fmt::Display for AnimationFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnimationFormat::Gif => write!(f, "gif"),
+            AnimationFormat::Apng => write!(f, "apng"),
+        }
+    }
+}
+
+/// How the generations of a run are drawn into an animation.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AnimationOptions {
+    /// Size of a cell in pixels, from 1 to 64.
+    pub cell_size: usize,
+    /// Colours of the cells.
+    pub palette: Palette,
+    /// What the colours show. The age and trail modes need a board tracking ages, see [`export_animation`].
+    pub color_mode: ColorMode,
+    /// Time each generation is shown, in milliseconds. GIF only stores hundredths of a second.
+    pub delay_ms: u32,
+    /// Whether to only show the smallest region holding the live cells of every generation.
+    pub crop: bool,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions {
+            cell_size: 4,
+            palette: Theme::Light.palette(),
+            color_mode: ColorMode::Plain,
+            delay_ms: 100,
+            crop: false,
+        }
+    }
+}
+
+/// Returns the current board followed by the boards of the next `generations` generations, advancing the game.
+pub fn record_generations(game_state: &mut GameState, generations: u64) -> Vec<Board> {
+    generation_boards(game_state, generations).collect()
+}
+
+// Yields the current board then those of the next `generations` generations, advancing the game as they are taken.
+fn generation_boards(game_state: &mut GameState, generations: u64) -> impl Iterator<Item = Board> + '_ {
+    iter::once(game_state.board.clone()).chain((0..generations).map(move |_| {
+        game_state.update();
+        game_state.board.clone()
+    }))
+}
+
+/// Draws each board into a frame. All the frames have the same size, that of the boards or, when cropping,
+/// of the region holding all their live cells.
+pub fn render_frames(boards: &[Board], options: &AnimationOptions) -> Vec<Frame> {
+    let Some(first) = boards.first() else {
+        return Vec::new();
+    };
+    let region = frame_region(first, boards, options);
+    boards.iter()
+        .map(|board| draw_region(board, region, options.cell_size, &options.palette, options.color_mode))
+        .collect()
+}
+
+// Returns the region of the boards drawn into the frames: the whole board, or the live cells when cropping.
+fn frame_region<B: Borrow<Board>>(first: &Board, boards: impl IntoIterator<Item = B>, options: &AnimationOptions) -> (usize, usize, usize, usize) {
+    let whole_board = (0, 0, first.grid_width, first.grid_height);
+    if options.crop { live_region(boards).unwrap_or(whole_board) } else { whole_board }
+}
+
+// Returns the union of the bounding boxes of the boards, None if they are all empty.
+fn live_region<B: Borrow<Board>>(boards: impl IntoIterator<Item = B>) -> Option<(usize, usize, usize, usize)> {
+    boards.into_iter()
+        .filter_map(|board| board.borrow().bounding_box())
+        .map(|(x, y, width, height)| (x, y, x + width, y + height))
+        .reduce(|(left, top, right, bottom), (x, y, end_x, end_y)| (left.min(x), top.min(y), right.max(end_x), bottom.max(end_y)))
+        .map(|(left, top, right, bottom)| (left, top, right - left, bottom - top))
+}
+
+/// Writes frames of the same size as a looping animation.
+pub fn write_animation(frames: &[Frame], format: AnimationFormat, delay_ms: u32, writer: impl Write) -> io::Result<()> {
+    let Some(first) = frames.first() else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "An animation needs at least one frame"));
+    };
+    let size = (first.width, first.height);
+    match format {
+        AnimationFormat::Gif => write_gif(frames, size, &shared_palette(frames), delay_ms, writer),
+        AnimationFormat::Apng => write_apng(frames, size, frames.len(), delay_ms, writer),
+    }
+}
+
+/// Runs the game for `generations` generations and writes them, preceded by the current one, as an animation.
+/// Age tracking is enabled on the board if the colour mode needs it.
+///
+/// Each frame is encoded as soon as it is drawn. The generations are run beforehand on a copy of the game to find
+/// the cropped region and, for a GIF, the colours, so the whole run is never held in memory.
+pub fn export_animation(game_state: &mut GameState, generations: u64, format: AnimationFormat, options: &AnimationOptions,
+                        writer: impl Write) -> io::Result<()> {
+    if options.color_mode.needs_ages() && game_state.board.ages.is_none() {
+        game_state.board.set_age_tracking(true);
+    }
+    let frame_count = usize::try_from(generations).ok().and_then(|generations| generations.checked_add(1))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Too many generations for an animation"))?;
+    let region = frame_region(&game_state.board, generation_boards(&mut game_state.clone(), generations), options);
+    let cell_size = options.cell_size.clamp(1, MAX_ZOOM as usize);
+    let size = (region.2 * cell_size, region.3 * cell_size);
+    let draw = |board: Board| draw_region(&board, region, options.cell_size, &options.palette, options.color_mode);
+
+    match format {
+        AnimationFormat::Gif => {
+            let colors = shared_palette(generation_boards(&mut game_state.clone(), generations).map(draw));
+            write_gif(generation_boards(game_state, generations).map(draw), size, &colors, options.delay_ms, writer)
+        }
+        AnimationFormat::Apng => write_apng(generation_boards(game_state, generations).map(draw), size, frame_count, options.delay_ms, writer),
+    }
+}
+
+// Writes the frames as a GIF of `size` pixels, with `colors` as the palette or, when empty, a palette for each frame.
+fn write_gif<F: Borrow<Frame>>(frames: impl IntoIterator<Item = F>, size: (usize, usize), colors: &[Rgba], delay_ms: u32,
+                               writer: impl Write) -> io::Result<()> {
+    let (width, height) = size;
+    let (Ok(gif_width), Ok(gif_height)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                  format!("A GIF is at most 65535 pixels wide and high, the frames are {}x{}", width, height)));
+    };
+    let delay = (delay_ms.saturating_add(5) / 10).min(u16::MAX as u32) as u16;
+
+    // Boards only use a few colours, so they usually share one palette and need no quantization.
+    let global_palette: Vec<u8> = colors.iter().flat_map(|color| color[..3].to_vec()).collect();
+    let mut encoder = gif::Encoder::new(writer, gif_width, gif_height, &global_palette).map_err(io::Error::other)?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(io::Error::other)?;
+
+    let indices: HashMap<Rgba, u8> = colors.iter().enumerate().map(|(index, color)| (*color, index as u8)).collect();
+    for frame in frames {
+        let frame = frame.borrow();
+        let mut gif_frame = if indices.is_empty() {
+            let mut pixels = frame.pixels.clone();
+            gif::Frame::from_rgba_speed(gif_width, gif_height, &mut pixels, 10)
+        } else {
+            let buffer = frame.pixels.chunks_exact(4).map(|pixel| indices[pixel]).collect();
+            gif::Frame { width: gif_width, height: gif_height, buffer: Cow::Owned(buffer), ..gif::Frame::default() }
+        };
+        gif_frame.delay = delay;
+        encoder.write_frame(&gif_frame).map_err(io::Error::other)?;
+    }
+    Ok(())
+}
+
+// Returns the colours used by the frames if there are at most 256 of them, otherwise an empty list.
+fn shared_palette<F: Borrow<Frame>>(frames: impl IntoIterator<Item = F>) -> Vec<Rgba> {
+    let mut colors = Vec::new();
+    for frame in frames {
+        for pixel in frame.borrow().pixels.chunks_exact(4) {
+            let color = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            if !colors.contains(&color) {
+                if colors.len() == 256 {
+                    return Vec::new();
+                }
+                colors.push(color);
+            }
+        }
+    }
+    colors
+}
+
+fn write_apng<F: Borrow<Frame>>(frames: impl IntoIterator<Item = F>, size: (usize, usize), frame_count: usize, delay_ms: u32,
+                                writer: impl Write) -> io::Result<()> {
+    let frame_count = u32::try_from(frame_count).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many frames for an APNG"))?;
+    let mut encoder = png::Encoder::new(writer, size.0 as u32, size.1 as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(frame_count, 0)?;
+    encoder.set_frame_delay(delay_ms.min(u16::MAX as u32) as u16, 1000)?;
+    let mut writer = encoder.write_header()?;
+    for frame in frames {
+        writer.write_image_data(&frame.borrow().pixels)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::cell_state::CellState;
+    use crate::game::pattern_io::parse_pattern;
+    use crate::game::placement::Placement;
+
+    fn blinker_game() -> GameState {
+        let mut game_state = GameState::new(10, 10, 1);
+        let blinker = parse_pattern("XXX").unwrap();
+        game_state.add_pattern(&blinker, &Placement { position: Some((3, 4)), ..Placement::default() }).unwrap();
+        game_state
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(AnimationFormat::from_path("out/run.GIF"), Some(AnimationFormat::Gif));
+        assert_eq!(AnimationFormat::from_path("run.apng"), Some(AnimationFormat::Apng));
+        assert_eq!(AnimationFormat::from_path("run.rle"), None);
+        assert_eq!("apng".parse::<AnimationFormat>(), Ok(AnimationFormat::Apng));
+        assert!("mp4".parse::<AnimationFormat>().is_err());
+    }
+
+    #[test]
+    fn test_render_frames_cropped() {
+        let mut game_state = blinker_game();
+        let boards = record_generations(&mut game_state, 2);
+        assert_eq!((boards.len(), game_state.generation), (3, 2));
+
+        let options = AnimationOptions { cell_size: 2, crop: true, ..AnimationOptions::default() };
+        let frames = render_frames(&boards, &options);
+        // The blinker spans 3x3 cells over its two phases.
+        assert!(frames.iter().all(|frame| (frame.width, frame.height) == (6, 6)));
+        assert_eq!(frames[0].pixel(0, 2), options.palette.alive);
+        assert_eq!(frames[1].pixel(0, 2), options.palette.dead);
+        assert_eq!(frames[1].pixel(2, 0), options.palette.alive);
+
+        let frames = render_frames(&boards, &AnimationOptions { cell_size: 1, ..options });
+        assert_eq!((frames[0].width, frames[0].height), (3, 3));
+        let whole = render_frames(&boards, &AnimationOptions::default());
+        assert_eq!((whole[0].width, whole[0].height), (40, 40));
+    }
+
+    #[test]
+    fn test_write_gif() {
+        let mut output = Vec::new();
+        let options = AnimationOptions { crop: true, ..AnimationOptions::default() };
+        export_animation(&mut blinker_game(), 3, AnimationFormat::Gif, &options, &mut output).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new().read_info(output.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (12, 12));
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 10);
+            frames += 1;
+        }
+        assert_eq!(frames, 4);
+    }
+
+    #[test]
+    fn test_write_apng() {
+        let mut game_state = blinker_game();
+        game_state.board.set_cell(0, 0, CellState::Alive);
+        let mut output = Vec::new();
+        let options = AnimationOptions { color_mode: ColorMode::Trail, delay_ms: 250, ..AnimationOptions::default() };
+        export_animation(&mut game_state, 1, AnimationFormat::Apng, &options, &mut output).unwrap();
+        assert!(game_state.board.ages.is_some());
+
+        let reader = png::Decoder::new(output.as_slice()).read_info().unwrap();
+        let control = reader.info().animation_control.unwrap();
+        assert_eq!((control.num_frames, control.num_plays), (2, 0));
+        assert_eq!((reader.info().width, reader.info().height), (40, 40));
+
+        assert!(write_animation(&[], AnimationFormat::Apng, 100, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_export_matches_recorded_frames() {
+        let options = AnimationOptions { color_mode: ColorMode::Age, crop: true, ..AnimationOptions::default() };
+        for format in [AnimationFormat::Gif, AnimationFormat::Apng] {
+            let mut game_state = blinker_game();
+            game_state.board.set_age_tracking(true);
+            let boards = record_generations(&mut game_state.clone(), 4);
+            let mut recorded = Vec::new();
+            write_animation(&render_frames(&boards, &options), format, options.delay_ms, &mut recorded).unwrap();
+
+            let mut streamed = Vec::new();
+            export_animation(&mut game_state, 4, format, &options, &mut streamed).unwrap();
+            assert_eq!(streamed, recorded);
+            assert_eq!(game_state.generation, 4);
+        }
+    }
+}
@@ -0,0 +1,20 @@
+/// Animated GIF and PNG files of a run.
+pub mod animation;
+/// Still PNG and SVG images of a board.
+pub mod snapshot;
+
+use crate::game::board::Board;
+use crate::render::camera::{Camera, MAX_ZOOM};
+use crate::render::frame::Frame;
+use crate::render::theme::{ColorMode, Palette};
+
+// Draws the `(x, y, width, height)` region of the board with cells of `cell_size` pixels, at most `MAX_ZOOM`.
+pub(crate) fn draw_region(board: &Board, region: (usize, usize, usize, usize), cell_size: usize, palette: &Palette, mode: ColorMode) -> Frame {
+    let (x, y, width, height) = region;
+    let cell_size = cell_size.clamp(1, MAX_ZOOM as usize);
+    let mut frame = Frame::new(width * cell_size, height * cell_size);
+    let mut camera = Camera::new(frame.width as f64, frame.height as f64, cell_size as f64);
+    camera.center = (x as f64 + width as f64 / 2.0, y as f64 + height as f64 / 2.0);
+    frame.draw_board(board, &camera, palette, mode);
+    frame
+}
@@ -0,0 +1,265 @@
+use std::fmt;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::export::draw_region;
+use crate::game::board::Board;
+use crate::game::cell_state::CellState;
+use crate::render::frame::{Frame, Rgba};
+use crate::render::theme::{ColorMode, Palette, Theme};
+
+/// The file formats of a snapshot.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SnapshotFormat {
+    /// A PNG image.
+    Png,
+    /// An SVG drawing, which scales without blurring.
+    Svg,
+}
+
+impl SnapshotFormat {
+    /// Guesses the format from the extension of a file name, `.png` or `.svg`.
+    pub fn from_path(path: &str) -> Option<SnapshotFormat> {
+        let extension = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        extension.parse().ok()
+    }
+}
+
+impl FromStr for SnapshotFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "png" => Ok(SnapshotFormat::Png),
+            "svg" => Ok(SnapshotFormat::Svg),
+            _ => Err(format!("Unknown snapshot format '{}', expected one of: png, svg", s)),
+        }
+    }
+}
+
+impl fmt::Display for SnapshotFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotFormat::Png => write!(f, "png"),
+            SnapshotFormat::Svg => write!(f, "svg"),
+        }
+    }
+}
+
+/// How a board is drawn into a snapshot.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SnapshotOptions {
+    /// Size of a cell in pixels, from 1 to 64.
+    pub cell_size: usize,
+    /// Colours of the cells and of the grid lines.
+    pub palette: Palette,
+    /// What the colours show in PNG snapshots. SVG snapshots only show live and dead cells.
+    pub color_mode: ColorMode,
+    /// Whether to draw lines between the cells, over the first pixel row and column of each cell.
+    pub grid_lines: bool,
+    /// Whether to only show the bounding box of the live cells.
+    pub crop: bool,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        SnapshotOptions {
+            cell_size: 4,
+            palette: Theme::Light.palette(),
+            color_mode: ColorMode::Plain,
+            grid_lines: false,
+            crop: false,
+        }
+    }
+}
+
+// Returns the region of the board shown by a snapshot. An empty board is shown whole even when cropping.
+fn snapshot_region(board: &Board, options: &SnapshotOptions) -> (usize, usize, usize, usize) {
+    let whole_board = (0, 0, board.grid_width, board.grid_height);
+    if options.crop { board.bounding_box().unwrap_or(whole_board) } else { whole_board }
+}
+
+/// Draws a board into a frame. With grid lines, the frame is one pixel wider and higher to close the grid.
+pub fn render_snapshot(board: &Board, options: &SnapshotOptions) -> Frame {
+    let region = snapshot_region(board, options);
+    let cells = draw_region(board, region, options.cell_size, &options.palette, options.color_mode);
+    if !options.grid_lines {
+        return cells;
+    }
+
+    let cell_size = cells.width / region.2.max(1);
+    let mut frame = Frame::new(cells.width + 1, cells.height + 1);
+    for y in 0..cells.height {
+        let row = &cells.pixels[y * cells.width * 4..(y + 1) * cells.width * 4];
+        frame.pixels[y * frame.width * 4..(y * frame.width + cells.width) * 4].copy_from_slice(row);
+    }
+    for column in 0..=region.2 {
+        frame.fill_rect(column * cell_size, 0, 1, frame.height, options.palette.grid);
+    }
+    for row in 0..=region.3 {
+        frame.fill_rect(0, row * cell_size, frame.width, 1, options.palette.grid);
+    }
+    frame
+}
+
+/// Writes a frame as a PNG image.
+pub fn write_png(frame: &Frame, writer: impl Write) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(writer, frame.width as u32, frame.height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&frame.pixels)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Writes a board as an SVG drawing in cell units, scaled to `cell_size` pixels per cell.
+///
+/// Live cells are merged into as few rectangles as possible: runs of live cells in a row, then identical runs in
+/// consecutive rows, so that large still patterns stay small.
+pub fn write_svg(board: &Board, options: &SnapshotOptions) -> String {
+    let (x, y, width, height) = snapshot_region(board, options);
+    let cell_size = options.cell_size.max(1);
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}" shape-rendering="crispEdges">"#,
+                     width * cell_size, height * cell_size, width, height);
+    let _ = writeln!(svg, r#"<rect width="{}" height="{}" fill="{}"/>"#, width, height, hex(options.palette.dead));
+
+    let _ = writeln!(svg, r#"<g fill="{}">"#, hex(options.palette.alive));
+    for (left, top, run_width, run_height) in live_rectangles(board, (x, y, width, height)) {
+        let _ = writeln!(svg, r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#, left, top, run_width, run_height);
+    }
+    svg.push_str("</g>\n");
+
+    if options.grid_lines {
+        let mut path = String::new();
+        for column in 0..=width {
+            let _ = write!(path, "M{} 0V{}", column, height);
+        }
+        for row in 0..=height {
+            let _ = write!(path, "M0 {}H{}", row, width);
+        }
+        let _ = writeln!(svg, r#"<path d="{}" stroke="{}" stroke-width="1" vector-effect="non-scaling-stroke"/>"#,
+                         path, hex(options.palette.grid));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// Returns rectangles covering the live cells of the region, relative to its top-left corner, sorted by row.
+fn live_rectangles(board: &Board, region: (usize, usize, usize, usize)) -> Vec<(usize, usize, usize, usize)> {
+    let (x, y, width, height) = region;
+    let mut rectangles = Vec::new();
+    // Runs (left, width) still growing downwards, with the row they started at.
+    let mut open: Vec<(usize, usize, usize)> = Vec::new();
+    for row in 0..=height {
+        let mut runs = Vec::new();
+        let mut column = 0;
+        while row < height && column < width {
+            if board.cells[x + column][y + row] == CellState::Alive {
+                let start = column;
+                while column < width && board.cells[x + column][y + row] == CellState::Alive {
+                    column += 1;
+                }
+                runs.push((start, column - start));
+            }
+            column += 1;
+        }
+
+        open.retain(|&(left, run_width, top)| {
+            let continues = runs.contains(&(left, run_width));
+            if !continues {
+                rectangles.push((left, top, run_width, row - top));
+            }
+            continues
+        });
+        for (left, run_width) in runs {
+            if !open.iter().any(|&(open_left, open_width, _)| (open_left, open_width) == (left, run_width)) {
+                open.push((left, run_width, row));
+            }
+        }
+    }
+    rectangles.sort_by_key(|&(left, top, _, _)| (top, left));
+    rectangles
+}
+
+fn hex(color: Rgba) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+/// Writes a snapshot of the board in the given format.
+pub fn write_snapshot(board: &Board, format: SnapshotFormat, options: &SnapshotOptions, mut writer: impl Write) -> io::Result<()> {
+    match format {
+        SnapshotFormat::Png => write_png(&render_snapshot(board, options), writer),
+        SnapshotFormat::Svg => writer.write_all(write_svg(board, options).as_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board_with_cells(width: usize, height: usize, cells: &[(usize, usize)]) -> Board {
+        let mut board = Board::new(width, height);
+        for (x, y) in cells {
+            board.set_cell(*x, *y, CellState::Alive);
+        }
+        board
+    }
+
+    #[test]
+    fn test_render_snapshot_with_grid() {
+        let board = board_with_cells(3, 2, &[(1, 1)]);
+        let options = SnapshotOptions { cell_size: 3, grid_lines: true, ..SnapshotOptions::default() };
+        let frame = render_snapshot(&board, &options);
+        assert_eq!((frame.width, frame.height), (10, 7));
+        assert_eq!(frame.pixel(0, 0), options.palette.grid);
+        assert_eq!(frame.pixel(9, 6), options.palette.grid);
+        assert_eq!(frame.pixel(1, 1), options.palette.dead);
+        assert_eq!(frame.pixel(4, 4), options.palette.alive);
+        assert_eq!(frame.pixel(3, 4), options.palette.grid);
+
+        let cropped = render_snapshot(&board, &SnapshotOptions { crop: true, grid_lines: false, ..options });
+        assert_eq!((cropped.width, cropped.height), (3, 3));
+    }
+
+    #[test]
+    fn test_write_png() {
+        let board = board_with_cells(4, 3, &[(0, 0)]);
+        let mut output = Vec::new();
+        write_snapshot(&board, SnapshotFormat::Png, &SnapshotOptions::default(), &mut output).unwrap();
+
+        let mut reader = png::Decoder::new(output.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (16, 12));
+        assert_eq!(pixels[..4], [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_svg_merges_runs() {
+        // A 2x2 block and a separate row of three cells.
+        let board = board_with_cells(6, 4, &[(0, 0), (1, 0), (0, 1), (1, 1), (3, 3), (4, 3), (5, 3)]);
+        assert_eq!(live_rectangles(&board, (0, 0, 6, 4)), vec![(0, 0, 2, 2), (3, 3, 3, 1)]);
+
+        let options = SnapshotOptions { cell_size: 10, ..SnapshotOptions::default() };
+        let svg = write_svg(&board, &options);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="60" height="40" viewBox="0 0 6 4""#));
+        assert!(svg.contains(r#"<rect x="0" y="0" width="2" height="2"/>"#));
+        assert_eq!(svg.matches("<rect").count(), 3);
+        assert!(!svg.contains("<path"));
+
+        let svg = write_svg(&board, &SnapshotOptions { crop: true, grid_lines: true, ..options });
+        assert!(svg.contains(r#"<rect x="3" y="3" width="3" height="1"/>"#));
+        assert!(svg.contains(r#"<path d="M0 0V4"#));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(SnapshotFormat::from_path("board.SVG"), Some(SnapshotFormat::Svg));
+        assert_eq!(SnapshotFormat::from_path("board.png"), Some(SnapshotFormat::Png));
+        assert_eq!(SnapshotFormat::from_path("board"), None);
+    }
+}
@@ -1,24 +1,67 @@
-use crate::game::rules::evolve_cell;
+use crate::game::rules::Rule;
 use crate::game::cell_state::CellState;
+use crate::game::paste_mode::PasteMode;
+use crate::game::pattern::Pattern;
+use crate::game::pattern_error::PatternError;
+use crate::game::pattern_io::{MAX_PATTERN_CELLS, MAX_PATTERN_SIZE};
+use crate::game::placement::Placement;
+use crate::game::topology::Topology;
+use crate::game::transform::Transform;
 
-use rand::{Rng, thread_rng};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng, thread_rng};
 
+/// The age of cells that have not been alive since age tracking started.
+pub const NEVER_ALIVE: u32 = u32::MAX;
 
+/// A finite grid of cells evolving under a Life-like rule.
 #[derive(Clone)]
 pub struct Board {
+    /// Number of columns.
     pub grid_width: usize,
+    /// Number of rows.
     pub grid_height: usize,
+    /// Cell states indexed as `cells[x][y]`.
     pub cells: Vec<Vec<CellState>>,
+    /// The rule the cells evolve with, Conway's by default.
+    pub rule: Rule,
+    /// How the edges of the board are handled, bounded by default.
+    pub topology: Topology,
+    /// Generations since each cell last changed state, indexed like `cells`, when age tracking is enabled.
+    pub ages: Option<Vec<Vec<u32>>>,
 }
 
 impl Board {
+    /// Creates a board with all its cells dead.
     pub fn new(grid_width: usize, grid_height: usize) -> Board {
         let cells = vec![vec![CellState::Dead; grid_height]; grid_width];
-        Board { grid_width, grid_height, cells }
+        Board { grid_width, grid_height, cells, rule: Rule::conway(), topology: Topology::Bounded, ages: None }
     }
 
+    /// Checks a board size coming from the command line, a link or a script before any cell is allocated: the board
+    /// must be at least one cell wide and high, and at most as large as a parsed pattern, see [`MAX_PATTERN_SIZE`] and
+    /// [`MAX_PATTERN_CELLS`].
+    pub fn check_size(grid_width: usize, grid_height: usize) -> Result<(), String> {
+        let cells = grid_width.checked_mul(grid_height);
+        if grid_width == 0 || grid_height == 0 || grid_width > MAX_PATTERN_SIZE || grid_height > MAX_PATTERN_SIZE
+            || cells.is_none_or(|cells| cells > MAX_PATTERN_CELLS) {
+            return Err(format!("The board must be 1 to {} cells wide and high, with at most {} cells, got {}x{}",
+                               MAX_PATTERN_SIZE, MAX_PATTERN_CELLS, grid_width, grid_height));
+        }
+        Ok(())
+    }
+
+    /// Makes each cell alive with the given probability.
     pub fn set_initial_state(&mut self, initial_alive_probability: f64) {
-        let mut rng = thread_rng();
+        self.fill_randomly(initial_alive_probability, &mut thread_rng());
+    }
+
+    /// Like [`Board::set_initial_state`], but reproducible: the same seed always gives the same board.
+    pub fn set_seeded_initial_state(&mut self, initial_alive_probability: f64, seed: u64) {
+        self.fill_randomly(initial_alive_probability, &mut StdRng::seed_from_u64(seed));
+    }
+
+    fn fill_randomly(&mut self, initial_alive_probability: f64, rng: &mut impl Rng) {
         for x in 0..self.grid_width {
             for y in 0..self.grid_height {
                 if rng.gen_bool(initial_alive_probability) {
@@ -28,45 +71,234 @@ impl Board {
         }
     }
 
-    pub fn add_pattern(&mut self, pattern: Vec<String>) {
-        let pattern_width = pattern[0].len();
-        let pattern_height = pattern.len();
+    /// Places a pattern as described by `placement`, centred when no position is given.
+    pub fn add_pattern(&mut self, pattern: &Pattern, placement: &Placement) -> Result<(), PatternError> {
+        let (x, y) = match placement.position {
+            Some(position) => position,
+            None => {
+                let (width, height) = placement.transform.transformed_size(pattern.width, pattern.height);
+                (self.grid_width.saturating_sub(width) / 2, self.grid_height.saturating_sub(height) / 2)
+            }
+        };
 
-        let start_x = (self.cells.len() - pattern_width) / 2;
-        let start_y = (self.cells[0].len() - pattern_height) / 2;
+        self.place_pattern(pattern, x, y, placement.transform, placement.paste_mode)
+    }
 
-        for (y, row) in pattern.iter().enumerate() {
-            for (x, char) in row.chars().enumerate() {
-                if char == 'X' {
-                    self.set_cell(start_x + x, start_y + y, CellState::Alive);
-                }
+    /// Pastes `pattern`, transformed, with its top-left corner at (`x`, `y`).
+    ///
+    /// Fails with [`PatternError::PatternTooLarge`] if the transformed pattern does not fit in the board.
+    pub fn place_pattern(&mut self, pattern: &Pattern, x: usize, y: usize, transform: Transform, paste_mode: PasteMode) -> Result<(), PatternError> {
+        let pattern = pattern.transformed(transform);
+
+        if x.saturating_add(pattern.width) > self.grid_width || y.saturating_add(pattern.height) > self.grid_height {
+            return Err(PatternError::PatternTooLarge {
+                pattern_width: pattern.width,
+                pattern_height: pattern.height,
+                x,
+                y,
+                board_width: self.grid_width,
+                board_height: self.grid_height,
+            });
+        }
+
+        for (pattern_x, column) in pattern.cells.iter().enumerate() {
+            for (pattern_y, pattern_cell) in column.iter().enumerate() {
+                let (board_x, board_y) = (x + pattern_x, y + pattern_y);
+                self.write_cell(board_x, board_y, paste_mode.apply(self.cells[board_x][board_y], *pattern_cell));
             }
         }
+        Ok(())
     }
 
-    fn get_cell(&self, x: usize, y: usize) -> CellState {
+    /// Returns the state of a cell, dead outside the board.
+    pub fn get_cell(&self, x: usize, y: usize) -> CellState {
         if x < self.grid_width && y < self.grid_height {
             return self.cells[x][y];
         }
         CellState::Dead
     }
 
+    /// Sets the state of a cell, ignoring coordinates outside the board.
     pub fn set_cell(&mut self, x: usize, y: usize, cell_state: CellState) {
         if x < self.grid_width && y < self.grid_height {
+            self.write_cell(x, y, cell_state);
+        }
+    }
+
+    // Sets a cell inside the board, restarting its age if its state changes.
+    fn write_cell(&mut self, x: usize, y: usize, cell_state: CellState) {
+        if self.cells[x][y] != cell_state {
             self.cells[x][y] = cell_state;
+            if let Some(ages) = &mut self.ages {
+                ages[x][y] = 0;
+            }
         }
     }
 
+    /// Starts or stops tracking how many generations each cell has spent in its current state.
+    ///
+    /// When tracking starts, live cells have an age of 0 and dead cells [`NEVER_ALIVE`].
+    pub fn set_age_tracking(&mut self, enabled: bool) {
+        if !enabled {
+            self.ages = None;
+        } else if self.ages.is_none() {
+            let ages = self.cells.iter()
+                .map(|column| column.iter().map(|cell| if *cell == CellState::Alive { 0 } else { NEVER_ALIVE }).collect())
+                .collect();
+            self.ages = Some(ages);
+        }
+    }
+
+    /// Returns the number of generations a cell has spent in its current state, if ages are tracked:
+    /// how long a live cell has lived, or how long ago a dead cell died.
+    pub fn age(&self, x: usize, y: usize) -> Option<u32> {
+        match &self.ages {
+            Some(ages) if x < self.grid_width && y < self.grid_height => Some(ages[x][y]),
+            _ => None,
+        }
+    }
+
+    /// Changes the size of the board, keeping its live cells and their ages, and returns how far the cells moved.
+    ///
+    /// The cells keep their place relative to the centre of the board. Those that would fall outside the new board
+    /// are moved back in, and the live cells of a pattern larger than the new board are cut on its right and bottom.
+    pub fn resize(&mut self, grid_width: usize, grid_height: usize) -> (isize, isize) {
+        let bounds = self.bounding_box();
+        let shift = |size: usize, new_size: usize, start: usize, extent: usize| -> isize {
+            let centred = start as isize + (new_size / 2) as isize - (size / 2) as isize;
+            centred.clamp(0, new_size.saturating_sub(extent) as isize) - start as isize
+        };
+        let (dx, dy) = match bounds {
+            Some((x, y, width, height)) => (shift(self.grid_width, grid_width, x, width), shift(self.grid_height, grid_height, y, height)),
+            None => (shift(self.grid_width, grid_width, 0, 0), shift(self.grid_height, grid_height, 0, 0)),
+        };
+
+        let mut resized = Board { rule: self.rule, topology: self.topology, ..Board::new(grid_width, grid_height) };
+        if self.ages.is_some() {
+            resized.ages = Some(vec![vec![NEVER_ALIVE; grid_height]; grid_width]);
+        }
+        for x in 0..grid_width {
+            for y in 0..grid_height {
+                let (from_x, from_y) = (x as isize - dx, y as isize - dy);
+                if !self.is_coord_in_board(from_x, from_y) {
+                    continue;
+                }
+                let (from_x, from_y) = (from_x as usize, from_y as usize);
+                resized.cells[x][y] = self.cells[from_x][from_y];
+                if let (Some(ages), Some(old_ages)) = (&mut resized.ages, &self.ages) {
+                    ages[x][y] = old_ages[from_x][from_y];
+                }
+            }
+        }
+        *self = resized;
+        (dx, dy)
+    }
+
+    /// Returns the number of live cells.
+    pub fn population(&self) -> usize {
+        self.cells.iter().flatten().filter(|cell| **cell == CellState::Alive).count()
+    }
+
+    /// Returns the smallest rectangle `(x, y, width, height)` holding all the live cells, if any.
+    pub fn bounding_box(&self) -> Option<(usize, usize, usize, usize)> {
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+        for (x, column) in self.cells.iter().enumerate() {
+            for (y, cell) in column.iter().enumerate() {
+                if *cell == CellState::Alive {
+                    let (min_x, min_y, max_x, max_y) = bounds.unwrap_or((x, y, x, y));
+                    bounds = Some((min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)));
+                }
+            }
+        }
+        bounds.map(|(min_x, min_y, max_x, max_y)| (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+    }
+
+    /// Returns the mean position of the centres of the live cells, if any.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        let mut count = 0usize;
+        let (mut sum_x, mut sum_y) = (0.0, 0.0);
+        for (x, column) in self.cells.iter().enumerate() {
+            for (y, cell) in column.iter().enumerate() {
+                if *cell == CellState::Alive {
+                    count += 1;
+                    sum_x += x as f64 + 0.5;
+                    sum_y += y as f64 + 0.5;
+                }
+            }
+        }
+        (count > 0).then(|| (sum_x / count as f64, sum_y / count as f64))
+    }
+
+    /// Copies the `width` x `height` region whose top-left corner is (`x`, `y`) into a pattern.
+    /// Cells outside the board are copied as dead.
+    pub fn extract(&self, x: usize, y: usize, width: usize, height: usize) -> Pattern {
+        let mut pattern = Pattern::new(width, height);
+        pattern.rule = Some(self.rule);
+        for (pattern_x, column) in pattern.cells.iter_mut().enumerate() {
+            for (pattern_y, cell) in column.iter_mut().enumerate() {
+                *cell = self.get_cell(x + pattern_x, y + pattern_y);
+            }
+        }
+        pattern
+    }
+
+    /// Sets every cell of the `width` x `height` region whose top-left corner is (`x`, `y`) to `cell_state`.
+    /// The part of the region outside the board is ignored.
+    pub fn fill_region(&mut self, x: usize, y: usize, width: usize, height: usize, cell_state: CellState) {
+        for x in self.region_columns(x, width) {
+            for y in self.region_rows(y, height) {
+                self.write_cell(x, y, cell_state);
+            }
+        }
+    }
+
+    /// Makes each cell of the region alive with the given probability and dead otherwise.
+    /// The part of the region outside the board is ignored.
+    pub fn randomize_region(&mut self, x: usize, y: usize, width: usize, height: usize, alive_probability: f64) {
+        let mut rng = thread_rng();
+        for x in self.region_columns(x, width) {
+            for y in self.region_rows(y, height) {
+                let cell_state = if rng.gen_bool(alive_probability) { CellState::Alive } else { CellState::Dead };
+                self.write_cell(x, y, cell_state);
+            }
+        }
+    }
+
+    fn region_columns(&self, x: usize, width: usize) -> std::ops::Range<usize> {
+        let end = x.saturating_add(width).min(self.grid_width);
+        x.min(end)..end
+    }
+
+    fn region_rows(&self, y: usize, height: usize) -> std::ops::Range<usize> {
+        let end = y.saturating_add(height).min(self.grid_height);
+        y.min(end)..end
+    }
+
+    /// Copies the live cells into a pattern cropped to their bounding box.
+    pub fn to_pattern(&self) -> Pattern {
+        match self.bounding_box() {
+            Some((x, y, width, height)) => self.extract(x, y, width, height),
+            None => self.extract(0, 0, 0, 0),
+        }
+    }
+
+    /// Advances the board by one generation.
     pub fn update(&mut self) {
         let mut new_state = self.cells.clone();
-        for x in 0..self.grid_width {
-            for y in 0..self.grid_height {
+        for (x, column) in new_state.iter_mut().enumerate() {
+            for (y, new_cell) in column.iter_mut().enumerate() {
                 let alive_neighbors = self.count_alive_neighbors(x, y);
-                let new_cell = evolve_cell(self.get_cell(x, y), alive_neighbors);
-                new_state[x][y] = new_cell;
+                *new_cell = self.rule.evolve(self.get_cell(x, y), alive_neighbors);
             }
         }
 
+        if let Some(ages) = &mut self.ages {
+            for (x, column) in ages.iter_mut().enumerate() {
+                for (y, age) in column.iter_mut().enumerate() {
+                    *age = if new_state[x][y] == self.cells[x][y] { age.saturating_add(1) } else { 0 };
+                }
+            }
+        }
         self.cells = new_state;
     }
 
@@ -88,12 +320,15 @@ impl Board {
         for i in 0..3 {
             for j in 0..3 {
                 if !(i == 1 && j == 1) {
-                    let coord_x_neighbor = x as isize + i as isize - 1;
-                    let coord_y_neighbor = y as isize + j as isize - 1;
-                    if self.is_coord_in_board(coord_x_neighbor, coord_y_neighbor) {
-                        if self.get_cell(coord_x_neighbor as usize, coord_y_neighbor as usize) == CellState::Alive {
-                            neighbors += 1;
-                        }
+                    let mut coord_x_neighbor = x as isize + i as isize - 1;
+                    let mut coord_y_neighbor = y as isize + j as isize - 1;
+                    if self.topology == Topology::Torus {
+                        coord_x_neighbor = coord_x_neighbor.rem_euclid(self.grid_width as isize);
+                        coord_y_neighbor = coord_y_neighbor.rem_euclid(self.grid_height as isize);
+                    }
+                    if self.is_coord_in_board(coord_x_neighbor, coord_y_neighbor)
+                        && self.get_cell(coord_x_neighbor as usize, coord_y_neighbor as usize) == CellState::Alive {
+                        neighbors += 1;
                     }
                 }
             }
@@ -123,6 +358,7 @@ impl Board {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::pattern_io::parse_pattern;
 
     #[test]
     fn test_new_board() {
@@ -133,6 +369,17 @@ mod tests {
         assert_eq!(board.cells[0].len(), 20);
     }
 
+    #[test]
+    fn test_check_size() {
+        assert!(Board::check_size(1, 1).is_ok());
+        assert!(Board::check_size(MAX_PATTERN_SIZE, MAX_PATTERN_CELLS / MAX_PATTERN_SIZE).is_ok());
+        assert!(Board::check_size(0, 10).is_err());
+        assert!(Board::check_size(MAX_PATTERN_SIZE + 1, 1).is_err());
+        assert!(Board::check_size(MAX_PATTERN_SIZE, MAX_PATTERN_SIZE).is_err());
+        assert!(Board::check_size(65536, 65536).is_err());
+        assert!(Board::check_size(usize::MAX, usize::MAX).is_err());
+    }
+
     #[test]
     fn test_initial_state() {
         let mut has_alive = false;
@@ -311,6 +558,246 @@ mod tests {
     }
 
 
+    fn glider() -> Pattern {
+        parse_pattern("  X\nX X\n XX\n").unwrap()
+    }
+
+    #[test]
+    fn test_add_pattern_centered() {
+        let mut board = Board::new(7, 7);
+        board.add_pattern(&glider(), &Placement::default()).unwrap();
+        assert_eq!(board.get_cell(4, 2), CellState::Alive);
+        assert_eq!(board.get_cell(2, 3), CellState::Alive);
+        assert_eq!(board.get_cell(4, 3), CellState::Alive);
+        assert_eq!(board.get_cell(3, 4), CellState::Alive);
+        assert_eq!(board.get_cell(4, 4), CellState::Alive);
+    }
+
+    #[test]
+    fn test_add_pattern_at_position_with_transform() {
+        let mut board = Board::new(10, 10);
+        let placement = Placement {
+            position: Some((0, 0)),
+            transform: Transform::Rotate180,
+            paste_mode: PasteMode::Or,
+        };
+        board.add_pattern(&glider(), &placement).unwrap();
+        // X X 0
+        // X 0 X
+        // X 0 0
+        assert_eq!(board.get_cell(0, 0), CellState::Alive);
+        assert_eq!(board.get_cell(1, 0), CellState::Alive);
+        assert_eq!(board.get_cell(0, 1), CellState::Alive);
+        assert_eq!(board.get_cell(2, 1), CellState::Alive);
+        assert_eq!(board.get_cell(0, 2), CellState::Alive);
+        assert_eq!(board.get_cell(2, 0), CellState::Dead);
+    }
+
+    #[test]
+    fn test_add_pattern_too_large() {
+        let mut board = Board::new(2, 2);
+        assert!(matches!(board.add_pattern(&glider(), &Placement::default()), Err(PatternError::PatternTooLarge { .. })));
+    }
+
+    #[test]
+    fn test_place_pattern_out_of_board() {
+        let mut board = Board::new(10, 10);
+        let pattern = glider();
+        assert!(board.place_pattern(&pattern, 8, 0, Transform::Identity, PasteMode::Or).is_err());
+        assert!(board.place_pattern(&pattern, 7, 7, Transform::Identity, PasteMode::Or).is_ok());
+    }
+
+    #[test]
+    fn test_place_pattern_paste_modes() {
+        let pattern = parse_pattern("XX").unwrap();
+
+        let mut board = Board::new(3, 1);
+        board.set_cell(0, 0, CellState::Alive);
+        board.set_cell(2, 0, CellState::Alive);
+        board.place_pattern(&pattern, 1, 0, Transform::Identity, PasteMode::Xor).unwrap();
+        assert_eq!(board.get_cell(1, 0), CellState::Alive);
+        assert_eq!(board.get_cell(2, 0), CellState::Dead);
+
+        let empty = Pattern::new(2, 1);
+        board.place_pattern(&empty, 0, 0, Transform::Identity, PasteMode::Or).unwrap();
+        assert_eq!(board.get_cell(0, 0), CellState::Alive);
+        board.place_pattern(&empty, 0, 0, Transform::Identity, PasteMode::Copy).unwrap();
+        assert_eq!(board.get_cell(0, 0), CellState::Dead);
+    }
+
+    #[test]
+    fn test_seeded_initial_state() {
+        let mut board = Board::new(10, 10);
+        let mut same_seed_board = Board::new(10, 10);
+        board.set_seeded_initial_state(0.5, 42);
+        same_seed_board.set_seeded_initial_state(0.5, 42);
+        assert_eq!(board.cells, same_seed_board.cells);
+    }
+
+    #[test]
+    fn test_torus_neighbors_wrap() {
+        let mut board = Board::new(5, 5);
+        board.topology = Topology::Torus;
+        board.set_cell(4, 4, CellState::Alive);
+        board.set_cell(0, 4, CellState::Alive);
+        board.set_cell(4, 0, CellState::Alive);
+        assert_eq!(board.count_alive_neighbors(0, 0), 3);
+
+        board.topology = Topology::Bounded;
+        assert_eq!(board.count_alive_neighbors(0, 0), 0);
+    }
+
+    #[test]
+    fn test_glider_crosses_torus_edge() {
+        let mut board = Board::new(6, 6);
+        board.topology = Topology::Torus;
+        board.add_pattern(&glider(), &Placement { position: Some((3, 3)), ..Placement::default() }).unwrap();
+        // A glider moves one cell diagonally every 4 generations, so after 24 it is back where it started.
+        let initial_cells = board.cells.clone();
+        for _ in 0..24 {
+            board.update();
+        }
+        assert_eq!(board.cells, initial_cells);
+    }
+
+    #[test]
+    fn test_update_with_rule() {
+        // Under HighLife (B36/S23) a dead cell with 6 neighbours is born.
+        let mut board = Board::new(3, 3);
+        board.rule = "B36/S23".parse().unwrap();
+        for (x, y) in [(0, 0), (1, 0), (2, 0), (0, 2), (1, 2), (2, 2)] {
+            board.set_cell(x, y, CellState::Alive);
+        }
+        board.update();
+        assert_eq!(board.get_cell(1, 1), CellState::Alive);
+    }
+
+    #[test]
+    fn test_population_and_bounding_box() {
+        let mut board = Board::new(10, 10);
+        assert_eq!(board.population(), 0);
+        assert_eq!(board.bounding_box(), None);
+
+        board.add_pattern(&glider(), &Placement { position: Some((4, 2)), ..Placement::default() }).unwrap();
+        assert_eq!(board.population(), 5);
+        assert_eq!(board.bounding_box(), Some((4, 2, 3, 3)));
+    }
+
+    #[test]
+    fn test_centroid() {
+        let mut board = Board::new(10, 10);
+        assert_eq!(board.centroid(), None);
+
+        board.set_cell(2, 3, CellState::Alive);
+        board.set_cell(4, 3, CellState::Alive);
+        assert_eq!(board.centroid(), Some((3.5, 3.5)));
+    }
+
+    #[test]
+    fn test_to_pattern() {
+        let mut board = Board::new(10, 10);
+        board.add_pattern(&glider(), &Placement { position: Some((4, 2)), ..Placement::default() }).unwrap();
+        let pattern = board.to_pattern();
+        assert_eq!(pattern.cells, glider().cells);
+        assert_eq!(pattern.rule, Some(Rule::conway()));
+
+        assert_eq!(Board::new(3, 3).to_pattern().width, 0);
+    }
+
+    #[test]
+    fn test_extract_outside_board() {
+        let mut board = Board::new(2, 2);
+        board.set_cell(1, 1, CellState::Alive);
+        let pattern = board.extract(1, 1, 3, 3);
+        assert_eq!(pattern.get_cell(0, 0), CellState::Alive);
+        assert_eq!(pattern.get_cell(2, 2), CellState::Dead);
+    }
+
+    #[test]
+    fn test_fill_region() {
+        let mut board = Board::new(4, 4);
+        board.fill_region(2, 1, 5, 2, CellState::Alive);
+        assert_eq!(board.population(), 4);
+        assert_eq!(board.bounding_box(), Some((2, 1, 2, 2)));
+
+        board.fill_region(3, 0, 1, 4, CellState::Dead);
+        assert_eq!(board.population(), 2);
+
+        board.fill_region(10, 10, 2, 2, CellState::Alive);
+        assert_eq!(board.population(), 2);
+    }
+
+    #[test]
+    fn test_randomize_region() {
+        let mut board = Board::new(6, 6);
+        board.randomize_region(1, 1, 2, 3, 1.0);
+        assert_eq!(board.bounding_box(), Some((1, 1, 2, 3)));
+
+        board.randomize_region(0, 0, 6, 2, 0.0);
+        assert_eq!(board.bounding_box(), Some((1, 2, 2, 2)));
+    }
+
+    #[test]
+    fn test_age_tracking() {
+        let mut board = Board::new(5, 5);
+        board.add_pattern(&parse_pattern("XXX").unwrap(), &Placement::default()).unwrap();
+        assert_eq!(board.age(2, 2), None);
+
+        board.set_age_tracking(true);
+        assert_eq!(board.age(2, 2), Some(0));
+        assert_eq!(board.age(2, 1), Some(NEVER_ALIVE));
+
+        board.update();
+        board.update();
+        // The centre of the blinker stays alive, the ends die and are born again.
+        assert_eq!(board.age(2, 2), Some(2));
+        assert_eq!(board.age(1, 2), Some(0));
+        assert_eq!(board.age(2, 1), Some(0));
+        board.update();
+        assert_eq!(board.age(2, 2), Some(3));
+        assert_eq!(board.get_cell(1, 2), CellState::Dead);
+        assert_eq!(board.age(1, 2), Some(0));
+        assert_eq!(board.age(0, 0), Some(NEVER_ALIVE));
+
+        board.set_cell(0, 0, CellState::Alive);
+        assert_eq!(board.age(0, 0), Some(0));
+
+        board.set_age_tracking(false);
+        assert_eq!(board.age(2, 2), None);
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut board = Board::new(10, 8);
+        board.topology = Topology::Torus;
+        board.add_pattern(&glider(), &Placement { position: Some((5, 4)), ..Placement::default() }).unwrap();
+        board.set_age_tracking(true);
+        board.update();
+        let before = board.clone();
+        let pattern = board.to_pattern();
+        assert_eq!(board.bounding_box(), Some((6, 4, 3, 3)));
+
+        // The cells follow the centre of the board, with their ages.
+        assert_eq!(board.resize(20, 12), (5, 2));
+        assert_eq!((board.grid_width, board.grid_height, board.topology), (20, 12, Topology::Torus));
+        assert_eq!(board.bounding_box(), Some((11, 6, 3, 3)));
+        assert_eq!(board.resize(10, 8), (-5, -2));
+        for (x, y) in (0..10).flat_map(|x| (0..8).map(move |y| (x, y))) {
+            assert_eq!((board.get_cell(x, y), board.age(x, y)), (before.get_cell(x, y), before.age(x, y)));
+        }
+
+        // Cells beyond the new edges are moved back in.
+        assert_eq!(board.resize(6, 6), (-3, -1));
+        assert_eq!(board.bounding_box(), Some((3, 3, 3, 3)));
+        assert_eq!(board.to_pattern().cells, pattern.cells);
+
+        // A pattern larger than the board is cut.
+        board.resize(2, 6);
+        assert_eq!(board.bounding_box().map(|(x, _, width, _)| (x, width)), Some((0, 2)));
+        board.resize(0, 0);
+        assert_eq!(board.population(), 0);
+    }
+
     #[test]
     fn test_coord_inside_board() {
          let board = Board::new(20, 10);
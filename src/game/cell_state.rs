@@ -1,5 +1,8 @@
-#[derive(Clone, PartialEq, Debug, Copy)]
+/// The state of a single cell.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Copy)]
 pub enum CellState {
+    /// A live cell.
     Alive,
+    /// A dead cell.
     Dead,
 }
\ No newline at end of file
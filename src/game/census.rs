@@ -0,0 +1,516 @@
+use crate::game::board::Board;
+use crate::game::cell_state::CellState;
+use crate::game::pattern::Pattern;
+use crate::game::rules::Rule;
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+/// The longest period looked for when identifying an object.
+pub const MAX_OBJECT_PERIOD: usize = 64;
+
+const WECHSLER_DIGITS: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+const ZERO_RUN_DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+// The apgcode, singular and plural names of common objects.
+const KNOWN_OBJECTS: &[(&str, &str, &str)] = &[
+    ("xs4_33", "block", "blocks"),
+    ("xs6_696", "beehive", "beehives"),
+    ("xs7_2596", "loaf", "loaves"),
+    ("xs5_253", "boat", "boats"),
+    ("xs4_252", "tub", "tubs"),
+    ("xs8_6996", "pond", "ponds"),
+    ("xs6_356", "ship", "ships"),
+    ("xs6_25a4", "barge", "barges"),
+    ("xs7_25ac", "long boat", "long boats"),
+    ("xs6_bd", "snake", "snakes"),
+    ("xs6_39c", "aircraft carrier", "aircraft carriers"),
+    ("xs7_178c", "eater", "eaters"),
+    ("xs12_g8o653z11", "ship-tie", "ship-ties"),
+    ("xp2_7", "blinker", "blinkers"),
+    ("xp2_7e", "toad", "toads"),
+    ("xp2_318c", "beacon", "beacons"),
+    ("xp3_co9nas0san9oczgoldlo0oldlogz1047210127401", "pulsar", "pulsars"),
+    ("xp15_4r4z4r4", "pentadecathlon", "pentadecathlons"),
+    ("xq4_153", "glider", "gliders"),
+    ("xq4_6frc", "lightweight spaceship", "lightweight spaceships"),
+    ("xq4_27dee6", "middleweight spaceship", "middleweight spaceships"),
+    ("xq4_27deee6", "heavyweight spaceship", "heavyweight spaceships"),
+];
+
+type Cells = Vec<(i32, i32)>;
+
+/// An object found on a board by [`separate_objects`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct CensusObject {
+    /// The live cells of the object, cropped to their bounding box.
+    pub pattern: Pattern,
+    /// Where the top-left corner of the pattern lies on the board.
+    pub position: (usize, usize),
+    /// The apgcode of the object, such as `xs4_33` for a block, or None when it is not a still life, an oscillator or a
+    /// spaceship of period up to [`MAX_OBJECT_PERIOD`].
+    pub apgcode: Option<String>,
+}
+
+impl CensusObject {
+    /// The common name of the object, such as `block`, when it has one.
+    pub fn name(&self) -> Option<&'static str> {
+        self.apgcode.as_deref().and_then(object_name)
+    }
+}
+
+/// The common name of the object with the given apgcode, such as `glider` for `xq4_153`.
+pub fn object_name(apgcode: &str) -> Option<&'static str> {
+    KNOWN_OBJECTS.iter().find(|(code, _, _)| *code == apgcode).map(|(_, name, _)| *name)
+}
+
+/// Identifies a pattern alone on an infinite plane by its apgcode, as used by apgsearch and Catagolue.
+///
+/// The code starts with `xs` and the population for still lifes, `xp` and the period for oscillators and `xq` and the
+/// period for spaceships, followed by the extended Wechsler encoding of the phase and orientation giving the shortest,
+/// then alphabetically first, encoding. Patterns that die or do not repeat within [`MAX_OBJECT_PERIOD`] generations have
+/// no apgcode.
+///
+/// ```
+/// use game_of_life::{apgcode, parse_pattern, Rule};
+///
+/// let glider = parse_pattern("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+/// assert_eq!(apgcode(&glider, &Rule::conway()), Some("xq4_153".to_string()));
+/// ```
+pub fn apgcode(pattern: &Pattern, rule: &Rule) -> Option<String> {
+    let cells: Cells = (0..pattern.width)
+        .flat_map(|x| (0..pattern.height).map(move |y| (x, y)))
+        .filter(|&(x, y)| pattern.get_cell(x, y) == CellState::Alive)
+        .map(|(x, y)| (x as i32, y as i32))
+        .collect();
+    cells_apgcode(&cells, rule)
+}
+
+/// Splits the live cells of a board into objects and identifies each of them.
+///
+/// Live cells less than three cells apart, horizontally and vertically, belong to the same object. Such a group is then
+/// split into its touching parts when every part evolves on its own as it does within the group, so that for instance a
+/// bi-block is counted as two blocks. Objects are listed from top to bottom, then left to right. The board is treated as
+/// bounded whatever its topology.
+pub fn separate_objects(board: &Board) -> Vec<CensusObject> {
+    let cells: Cells = (0..board.grid_width)
+        .flat_map(|x| (0..board.grid_height).map(move |y| (x, y)))
+        .filter(|&(x, y)| board.cells[x][y] == CellState::Alive)
+        .map(|(x, y)| (x as i32, y as i32))
+        .collect();
+
+    let mut objects: Vec<CensusObject> = components(&cells, 2)
+        .into_iter()
+        .flat_map(|group| split_pseudo_object(group, &board.rule))
+        .map(|cells| {
+            let ((left, top), shape) = normalize(&cells);
+            let width = shape.iter().map(|&(x, _)| x as usize + 1).max().unwrap_or(0);
+            let height = shape.iter().map(|&(_, y)| y as usize + 1).max().unwrap_or(0);
+            let mut pattern = Pattern::new(width, height);
+            for &(x, y) in &shape {
+                pattern.set_cell(x as usize, y as usize, CellState::Alive);
+            }
+            pattern.rule = Some(board.rule);
+            CensusObject { pattern, position: (left as usize, top as usize), apgcode: cells_apgcode(&cells, &board.rule) }
+        })
+        .collect();
+    objects.sort_by_key(|object| (object.position.1, object.position.0));
+    objects
+}
+
+/// How many objects of each kind a board holds, written like `12 blocks, 5 blinkers, 2 gliders`.
+///
+/// ```
+/// use game_of_life::{parse_pattern, Board, Census, Placement};
+///
+/// let mut board = Board::new(20, 10);
+/// for (x, pattern) in [(1, "XX\nXX"), (6, "XX\nXX"), (12, "XXX")] {
+///     let pattern = parse_pattern(pattern).unwrap();
+///     board.add_pattern(&pattern, &Placement { position: Some((x, 4)), ..Placement::default() }).unwrap();
+/// }
+/// assert_eq!(Census::of_board(&board).to_string(), "2 blocks, 1 blinker");
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Census {
+    counts: BTreeMap<String, usize>,
+    unidentified: usize,
+}
+
+impl Census {
+    /// Separates and counts the objects of a board.
+    pub fn of_board(board: &Board) -> Census {
+        let mut census = Census::default();
+        for object in separate_objects(board) {
+            census.add(&object);
+        }
+        census
+    }
+
+    /// Counts one more object.
+    pub fn add(&mut self, object: &CensusObject) {
+        self.add_count(object.apgcode.as_deref(), 1);
+    }
+
+    /// Counts `count` more objects with the given apgcode, or without one.
+    pub fn add_count(&mut self, apgcode: Option<&str>, count: usize) {
+        match apgcode {
+            Some(apgcode) => *self.counts.entry(apgcode.to_string()).or_default() += count,
+            None => self.unidentified += count,
+        }
+    }
+
+    /// Adds the counts of another census, for instance to total the census of many boards.
+    pub fn merge(&mut self, other: &Census) {
+        for (apgcode, &count) in &other.counts {
+            self.add_count(Some(apgcode), count);
+        }
+        self.add_count(None, other.unidentified);
+    }
+
+    /// The number of objects with the given apgcode.
+    pub fn count(&self, apgcode: &str) -> usize {
+        self.counts.get(apgcode).copied().unwrap_or(0)
+    }
+
+    /// The number of objects without an apgcode.
+    pub fn unidentified(&self) -> usize {
+        self.unidentified
+    }
+
+    /// The number of objects counted.
+    pub fn total(&self) -> usize {
+        self.counts.values().sum::<usize>() + self.unidentified
+    }
+
+    /// The apgcodes counted and their counts, most common first.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        let mut counts: Vec<(&str, usize)> = self.counts.iter().map(|(apgcode, &count)| (apgcode.as_str(), count)).collect();
+        counts.sort_by_key(|&(apgcode, count)| (Reverse(count), apgcode));
+        counts.into_iter()
+    }
+}
+
+impl fmt::Display for Census {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = self
+            .iter()
+            .map(|(apgcode, count)| match KNOWN_OBJECTS.iter().find(|(code, _, _)| *code == apgcode) {
+                Some((_, singular, _)) if count == 1 => format!("1 {}", singular),
+                Some((_, _, plural)) => format!("{} {}", count, plural),
+                None => format!("{} {}", count, apgcode),
+            })
+            .collect();
+        match self.unidentified {
+            0 => {}
+            1 => parts.push("1 unidentified object".to_string()),
+            count => parts.push(format!("{} unidentified objects", count)),
+        }
+        if parts.is_empty() {
+            write!(f, "no objects")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+// Groups cells closer than `range + 1` cells horizontally and vertically, in the order of their first cell.
+fn components(cells: &[(i32, i32)], range: i32) -> Vec<Cells> {
+    let index: HashMap<(i32, i32), usize> = cells.iter().enumerate().map(|(i, &cell)| (cell, i)).collect();
+    let mut parents: Vec<usize> = (0..cells.len()).collect();
+    for (i, &(x, y)) in cells.iter().enumerate() {
+        for dx in -range..=range {
+            for dy in -range..=range {
+                if let Some(&j) = index.get(&(x + dx, y + dy)) {
+                    let (root_i, root_j) = (find_root(&mut parents, i), find_root(&mut parents, j));
+                    parents[root_i.max(root_j)] = root_i.min(root_j);
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<Cells> = Vec::new();
+    let mut group_of_root: HashMap<usize, usize> = HashMap::new();
+    for (i, &cell) in cells.iter().enumerate() {
+        let root = find_root(&mut parents, i);
+        let group = *group_of_root.entry(root).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(cell);
+    }
+    groups
+}
+
+fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+// Splits a group into its touching parts when each of them evolves as it does within the group, over a period of the
+// group, or over MAX_OBJECT_PERIOD generations when the group does not repeat.
+fn split_pseudo_object(group: Cells, rule: &Rule) -> Vec<Cells> {
+    let parts = components(&group, 1);
+    if parts.len() < 2 {
+        return vec![group];
+    }
+    let generations = find_period(&group, rule).map_or(MAX_OBJECT_PERIOD, |(phases, _)| phases.len());
+
+    let mut whole: HashSet<(i32, i32)> = group.iter().copied().collect();
+    let mut separate: Vec<HashSet<(i32, i32)>> = parts.iter().map(|part| part.iter().copied().collect()).collect();
+    for _ in 0..generations {
+        whole = step(&whole, rule);
+        separate = separate.iter().map(|part| step(part, rule)).collect();
+        let population: usize = separate.iter().map(HashSet::len).sum();
+        if population != whole.len() || !separate.iter().flatten().all(|cell| whole.contains(cell)) {
+            return vec![group];
+        }
+    }
+    parts
+}
+
+fn cells_apgcode(cells: &[(i32, i32)], rule: &Rule) -> Option<String> {
+    let (phases, displacement) = find_period(cells, rule)?;
+    let prefix = match (phases.len(), displacement) {
+        (1, (0, 0)) => format!("xs{}", cells.len()),
+        (period, (0, 0)) => format!("xp{}", period),
+        (period, _) => format!("xq{}", period),
+    };
+    let encoding = phases
+        .iter()
+        .flat_map(|phase| (0..8).map(move |orientation| wechsler(&normalize(&orient(phase, orientation)).1)))
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))?;
+    Some(format!("{}_{}", prefix, encoding))
+}
+
+// The phases of a pattern up to the generation it repeats in, and how far it moved by then.
+fn find_period(cells: &[(i32, i32)], rule: &Rule) -> Option<(Vec<Cells>, (i32, i32))> {
+    let (origin, shape) = normalize(cells);
+    let mut phases = vec![cells.to_vec()];
+    let mut current: HashSet<(i32, i32)> = cells.iter().copied().collect();
+    for _ in 0..MAX_OBJECT_PERIOD {
+        current = step(&current, rule);
+        if current.is_empty() {
+            return None;
+        }
+        let next: Cells = current.iter().copied().collect();
+        let (next_origin, next_shape) = normalize(&next);
+        if next_shape == shape {
+            return Some((phases, (next_origin.0 - origin.0, next_origin.1 - origin.1)));
+        }
+        phases.push(next);
+    }
+    None
+}
+
+// The next generation of cells on an infinite plane. Births on zero neighbours are ignored.
+fn step(cells: &HashSet<(i32, i32)>, rule: &Rule) -> HashSet<(i32, i32)> {
+    let mut neighbours: HashMap<(i32, i32), u8> = HashMap::new();
+    for &(x, y) in cells {
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if (dx, dy) != (0, 0) {
+                    *neighbours.entry((x + dx, y + dy)).or_default() += 1;
+                }
+            }
+        }
+    }
+    let isolated = cells.iter().filter(|cell| !neighbours.contains_key(cell) && rule.evolve(CellState::Alive, 0) == CellState::Alive);
+    neighbours
+        .iter()
+        .filter(|&(cell, &count)| {
+            let state = if cells.contains(cell) { CellState::Alive } else { CellState::Dead };
+            rule.evolve(state, count) == CellState::Alive
+        })
+        .map(|(&cell, _)| cell)
+        .chain(isolated.copied())
+        .collect()
+}
+
+// One of the eight rotations and reflections of the cells.
+fn orient(cells: &[(i32, i32)], orientation: u8) -> Cells {
+    cells
+        .iter()
+        .map(|&(x, y)| {
+            let (x, y) = if orientation & 4 != 0 { (y, x) } else { (x, y) };
+            let x = if orientation & 1 != 0 { -x } else { x };
+            let y = if orientation & 2 != 0 { -y } else { y };
+            (x, y)
+        })
+        .collect()
+}
+
+// The top-left corner of the cells, and the cells moved to start at (0, 0) and sorted.
+fn normalize(cells: &[(i32, i32)]) -> ((i32, i32), Cells) {
+    let left = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let top = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    let mut shape: Cells = cells.iter().map(|&(x, y)| (x - left, y - top)).collect();
+    shape.sort_unstable();
+    ((left, top), shape)
+}
+
+// The extended Wechsler format: strips of five rows, each column a base 32 digit with the top cell as its lowest bit,
+// the strips separated by 'z'. Trailing empty columns are dropped and runs of empty columns written 'w' for two, 'x'
+// for three and 'y' followed by a digit for four or more.
+fn wechsler(shape: &[(i32, i32)]) -> String {
+    let width = shape.iter().map(|&(x, _)| x as usize + 1).max().unwrap_or(0);
+    let height = shape.iter().map(|&(_, y)| y as usize + 1).max().unwrap_or(0);
+    let mut strips = Vec::new();
+    for top in (0..height).step_by(5) {
+        let mut columns = vec![0usize; width];
+        for &(x, y) in shape.iter().filter(|&&(_, y)| (top..top + 5).contains(&(y as usize))) {
+            columns[x as usize] |= 1 << (y as usize - top);
+        }
+        let length = columns.iter().rposition(|&column| column != 0).map_or(0, |last| last + 1);
+
+        let mut strip = String::new();
+        let mut zeros = 0;
+        for &column in columns[..length].iter().chain(std::iter::once(&1)) {
+            if column == 0 {
+                zeros += 1;
+                continue;
+            }
+            while zeros >= 4 {
+                let run = zeros.min(ZERO_RUN_DIGITS.len() + 3);
+                strip.push('y');
+                strip.push(ZERO_RUN_DIGITS[run - 4] as char);
+                zeros -= run;
+            }
+            strip.push_str(["", "0", "w", "x"][zeros]);
+            zeros = 0;
+            strip.push(WECHSLER_DIGITS[column] as char);
+        }
+        // The sentinel column pushed last.
+        strip.pop();
+        strips.push(strip);
+    }
+    strips.join("z")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::pattern_io::parse_pattern;
+    use crate::game::placement::Placement;
+
+    fn code(text: &str) -> Option<String> {
+        apgcode(&parse_pattern(text).unwrap(), &Rule::conway())
+    }
+
+    fn board_with(width: usize, height: usize, patterns: &[(usize, usize, &str)]) -> Board {
+        let mut board = Board::new(width, height);
+        for &(x, y, text) in patterns {
+            let pattern = parse_pattern(text).unwrap();
+            board.add_pattern(&pattern, &Placement { position: Some((x, y)), ..Placement::default() }).unwrap();
+        }
+        board
+    }
+
+    #[test]
+    fn common_objects_have_their_catagolue_apgcodes() {
+        let objects = [
+            ("XX\nXX", "block"),
+            (".X.\nX.X\nX.X\n.X.", "beehive"),
+            (".XX.\nX..X\n.X.X\n..X.", "loaf"),
+            ("XX.\nX.X\n.X.", "boat"),
+            (".X.\nX.X\n.X.", "tub"),
+            (".XX.\nX..X\nX..X\n.XX.", "pond"),
+            ("XX.\nX.X\n.XX", "ship"),
+            (".X..\nX.X.\n.X.X\n..X.", "barge"),
+            (".X..\nX.X.\n.X.X\n..XX", "long boat"),
+            ("XX.X\nX.XX", "snake"),
+            ("XX..\nX..X\n..XX", "aircraft carrier"),
+            ("XX..\nX.X.\n..X.\n..XX", "eater"),
+            ("XX....\nX.X...\n.XX...\n...XX.\n...X.X\n....XX", "ship-tie"),
+            ("XXX", "blinker"),
+            (".XXX\nXXX.", "toad"),
+            ("XX..\nX...\n...X\n..XX", "beacon"),
+            (".X.\n..X\nXXX", "glider"),
+            (".X..X\nX....\nX...X\nXXXX.", "lightweight spaceship"),
+            ("..X...\nX...X.\n.....X\nX....X\n.XXXXX", "middleweight spaceship"),
+            ("..XX...\nX....X.\n......X\nX.....X\n.XXXXXX", "heavyweight spaceship"),
+            ("..X....X..\nXX.XXXX.XX\n..X....X..", "pentadecathlon"),
+            (
+                "..XXX...XXX..\n.............\nX....X.X....X\nX....X.X....X\nX....X.X....X\n..XXX...XXX..\n.............\n\
+                 ..XXX...XXX..\nX....X.X....X\nX....X.X....X\nX....X.X....X\n.............\n..XXX...XXX..",
+                "pulsar",
+            ),
+        ];
+        for (text, name) in objects {
+            let apgcode = code(text).unwrap_or_else(|| panic!("No apgcode for the {}", name));
+            assert_eq!(object_name(&apgcode), Some(name), "{} is not a {}", apgcode, name);
+        }
+    }
+
+    #[test]
+    fn apgcodes_do_not_depend_on_the_phase_or_orientation() {
+        assert_eq!(code("X\nX\nX"), code("XXX"));
+        assert_eq!(code("X.X\n.XX\n.X."), Some("xq4_153".to_string()));
+        assert_eq!(code("XXX\nX..\n.X."), Some("xq4_153".to_string()));
+    }
+
+    #[test]
+    fn apgcodes_compress_empty_columns_and_rows() {
+        assert_eq!(wechsler(&[(0, 0), (1, 0), (3, 0), (6, 0)]), "1101w1");
+        assert_eq!(wechsler(&[(0, 0), (40, 0)]), "1yz1");
+        assert_eq!(wechsler(&[(0, 0), (0, 4), (2, 5), (0, 11)]), "hzw1z2");
+        assert_eq!(wechsler(&[(0, 0), (0, 12)]), "1zz4");
+    }
+
+    #[test]
+    fn patterns_that_die_or_do_not_settle_have_no_apgcode() {
+        assert_eq!(code("XX"), None);
+        // The R-pentomino keeps growing for over a thousand generations.
+        assert_eq!(code(".XX\nXX.\n.X."), None);
+    }
+
+    #[test]
+    fn objects_are_separated_and_identified() {
+        let board = board_with(30, 20, &[(1, 1, "XX\nXX"), (8, 1, "XXX"), (2, 10, ".X.\n..X\nXXX"), (20, 12, ".X.\nX.X\nX.X\n.X.")]);
+        let objects = separate_objects(&board);
+        let names: Vec<_> = objects.iter().map(|object| (object.position, object.name())).collect();
+        assert_eq!(names, vec![((1, 1), Some("block")), ((8, 1), Some("blinker")), ((2, 10), Some("glider")), ((20, 12), Some("beehive"))]);
+        assert_eq!(objects[0].pattern.width, 2);
+    }
+
+    #[test]
+    fn nearby_cells_form_one_object() {
+        // Two cells apart, the tub is not split into its four cells.
+        let board = board_with(10, 10, &[(3, 3, ".X.\nX.X\n.X.")]);
+        assert_eq!(separate_objects(&board).len(), 1);
+
+        // A block and a blinker far enough not to interact, but close enough to be grouped.
+        let board = board_with(12, 12, &[(2, 2, "XX\nXX"), (5, 5, "XXX")]);
+        assert_eq!(Census::of_board(&board).to_string(), "1 blinker, 1 block");
+    }
+
+    #[test]
+    fn pseudo_still_lifes_are_split_but_strict_ones_are_not() {
+        let bi_block = board_with(10, 10, &[(2, 2, "XX.XX\nXX.XX")]);
+        assert_eq!(Census::of_board(&bi_block).to_string(), "2 blocks");
+
+        // The two halves of this still life die on their own.
+        let table_on_table = board_with(10, 10, &[(2, 2, "X..X\nXXXX\n....\nXXXX\nX..X")]);
+        let objects = separate_objects(&table_on_table);
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].apgcode.as_deref(), Some("xs12_raar"));
+    }
+
+    #[test]
+    fn censuses_count_and_merge() {
+        let board = board_with(40, 20, &[(1, 1, "XX\nXX"), (6, 1, "XX\nXX"), (12, 1, "XXX"), (20, 1, "XX\nX.X\n.X."), (28, 10, ".XX\nXX.\n.X.")]);
+        let mut census = Census::of_board(&board);
+        assert_eq!(census.count("xs4_33"), 2);
+        assert_eq!(census.unidentified(), 1);
+        assert_eq!(census.to_string(), "2 blocks, 1 blinker, 1 boat, 1 unidentified object");
+
+        census.merge(&Census::of_board(&board_with(12, 4, &[(1, 1, "XXX"), (6, 1, "XXX")])));
+        assert_eq!(census.count("xp2_7"), 3);
+        assert_eq!(census.total(), 7);
+        assert_eq!(census.iter().next(), Some(("xp2_7", 3)));
+        assert_eq!(Census::default().to_string(), "no objects");
+    }
+}
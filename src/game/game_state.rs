@@ -1,29 +1,66 @@
 use crate::game::board::Board;
+use crate::game::pattern::Pattern;
+use crate::game::pattern_error::PatternError;
+use crate::game::placement::Placement;
+use crate::game::rules::Rule;
+use crate::game::topology::Topology;
 
+/// The state of a game, driven by the views.
 #[derive(Clone)]
 pub struct GameState {
+    /// The board being simulated.
     pub board: Board,
+    /// Number of generations computed since the game started.
+    pub generation: u64,
 }
 
 impl GameState {
+    /// Creates an empty game filling a `width` x `height` pixel area with cells of `cell_size` pixels.
     pub fn new(width: usize, height: usize, cell_size: usize) -> Self {
         let grid_width = width / cell_size;
         let grid_height = height / cell_size;
 
         GameState {
             board: Board::new(grid_width, grid_height),
+            generation: 0,
         }
     }
 
+    /// Fills the board randomly, see [`Board::set_initial_state`].
     pub fn init(&mut self, initial_alive_probability: f64) {
         self.board.set_initial_state(initial_alive_probability);
     }
 
-    pub fn add_pattern(&mut self, pattern: Vec<String>) {
-        self.board.add_pattern(pattern);
+    /// Fills the board randomly from a seed, see [`Board::set_seeded_initial_state`].
+    pub fn init_with_seed(&mut self, initial_alive_probability: f64, seed: u64) {
+        self.board.set_seeded_initial_state(initial_alive_probability, seed);
     }
 
+    /// Sets the rule the board evolves with.
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.board.rule = rule;
+    }
+
+    /// Sets how the edges of the board are handled.
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.board.topology = topology;
+    }
+
+    /// Places a pattern on the board, see [`Board::add_pattern`].
+    pub fn add_pattern(&mut self, pattern: &Pattern, placement: &Placement) -> Result<(), PatternError> {
+        self.board.add_pattern(pattern, placement)
+    }
+
+    /// Advances the game by one generation.
     pub fn update(&mut self) {
         self.board.update();
+        self.generation += 1;
+    }
+
+    /// Advances the game by `generations` generations.
+    pub fn advance(&mut self, generations: u64) {
+        for _ in 0..generations {
+            self.update();
+        }
     }
 }
\ No newline at end of file
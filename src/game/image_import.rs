@@ -0,0 +1,317 @@
+use crate::game::board::Board;
+use crate::game::cell_state::CellState;
+use crate::game::pattern::Pattern;
+use crate::game::pattern_error::PatternError;
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+/// The most pixels an imported image can have.
+pub const MAX_IMAGE_PIXELS: usize = 1 << 24;
+
+/// How the pixels of an image are turned into cells.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ImageImportOptions {
+    /// Brightness, from 0 to 255, below which a pixel is a live cell.
+    pub threshold: u8,
+    /// Whether the live cells are the bright pixels instead, as in screenshots of dark themes.
+    pub invert: bool,
+    /// Size of a cell in pixels, or None to detect the cell grid of a screenshot from the edges of its cells.
+    pub cell_size: Option<usize>,
+}
+
+impl Default for ImageImportOptions {
+    fn default() -> Self {
+        ImageImportOptions { threshold: 128, invert: false, cell_size: Some(1) }
+    }
+}
+
+// An image reduced to whether each pixel is a live cell, indexed as `alive[y * width + x]`.
+struct Mask {
+    width: usize,
+    height: usize,
+    alive: Vec<bool>,
+}
+
+impl Mask {
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.alive[y * self.width + x]
+    }
+}
+
+/// Returns whether a file name has the extension of an image format [`parse_image_pattern`] reads.
+pub fn is_image_path(path: &str) -> bool {
+    Path::new(path).extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| ["png", "ppm", "pgm"].contains(&extension.to_ascii_lowercase().as_str()))
+}
+
+/// Reads an image file and turns it into a pattern, see [`parse_image_pattern`].
+pub fn read_image_pattern(path: &str, options: &ImageImportOptions) -> Result<Pattern, PatternError> {
+    let bytes = fs::read(path).map_err(|source| PatternError::Io { path: path.to_string(), source })?;
+    parse_image_pattern(&bytes, options)
+}
+
+/// Turns a PNG, PPM or PGM image into a pattern cropped to its live cells.
+///
+/// Each pixel darker than the threshold is alive, or brighter when inverted, and transparent pixels are dead.
+/// When the cells span several pixels, each cell takes the state of its centre pixel.
+pub fn parse_image_pattern(bytes: &[u8], options: &ImageImportOptions) -> Result<Pattern, PatternError> {
+    let (width, height, brightness, opaque) = if bytes.starts_with(b"\x89PNG") {
+        decode_png(bytes)?
+    } else if bytes.starts_with(b"P3") || bytes.starts_with(b"P6") || bytes.starts_with(b"P2") || bytes.starts_with(b"P5") {
+        decode_netpbm(bytes)?
+    } else {
+        return Err(PatternError::Image("Unknown image format, expected PNG, PPM or PGM".to_string()));
+    };
+    let alive = brightness.iter().zip(opaque)
+        .map(|(&value, opaque)| opaque && ((value < options.threshold) != options.invert))
+        .collect();
+    let mask = Mask { width, height, alive };
+
+    let (cell_size, offset_x, offset_y) = match options.cell_size {
+        Some(cell_size) => (cell_size.max(1), 0, 0),
+        None => detect_grid(&mask),
+    };
+    let columns = (width.saturating_sub(offset_x)).div_ceil(cell_size);
+    let rows = (height.saturating_sub(offset_y)).div_ceil(cell_size);
+    let mut board = Board::new(columns, rows);
+    for x in 0..columns {
+        for y in 0..rows {
+            let pixel_x = (offset_x + x * cell_size + (cell_size - 1) / 2).min(width - 1);
+            let pixel_y = (offset_y + y * cell_size + (cell_size - 1) / 2).min(height - 1);
+            if mask.get(pixel_x, pixel_y) {
+                board.set_cell(x, y, CellState::Alive);
+            }
+        }
+    }
+    if board.population() == 0 {
+        return Err(PatternError::Image("The image has no live cell, try another threshold or inverting it".to_string()));
+    }
+    let mut pattern = board.to_pattern();
+    pattern.rule = None;
+    Ok(pattern)
+}
+
+// Returns the cell size and the position of the first cell boundary along each axis.
+//
+// Edges of the same kind (dead to alive, or alive to dead) are a whole number of cells apart, with or without grid
+// lines between the cells, so the cell size is the greatest common divisor of their distances. Cells are assumed to
+// be square. When all the edges of a kind line up, as for a single block of cells, the block is taken as one cell.
+fn detect_grid(mask: &Mask) -> (usize, usize, usize) {
+    let rows: Vec<Vec<bool>> = (0..mask.height).map(|y| (0..mask.width).map(|x| mask.get(x, y)).collect()).collect();
+    let columns: Vec<Vec<bool>> = (0..mask.width).map(|x| (0..mask.height).map(|y| mask.get(x, y)).collect()).collect();
+    let (row_rising, row_falling) = edges(&rows);
+    let (column_rising, column_falling) = edges(&columns);
+
+    let cell_size = [&row_rising, &row_falling, &column_rising, &column_falling].into_iter()
+        .flat_map(|positions| positions.iter().map(|position| position - positions.first().unwrap()))
+        .fold(0, gcd);
+    let cell_size = if cell_size > 0 {
+        cell_size
+    } else {
+        shortest_run(&rows).into_iter().chain(shortest_run(&columns)).min().unwrap_or(1)
+    };
+    let offset = |rising: &BTreeSet<usize>| rising.first().map_or(0, |edge| edge % cell_size);
+    (cell_size, offset(&row_rising), offset(&column_rising))
+}
+
+// Returns the positions along the lines where a dead pixel is followed by a live one, and the reverse.
+fn edges(lines: &[Vec<bool>]) -> (BTreeSet<usize>, BTreeSet<usize>) {
+    let (mut rising, mut falling) = (BTreeSet::new(), BTreeSet::new());
+    for line in lines {
+        for position in 1..line.len() {
+            match (line[position - 1], line[position]) {
+                (false, true) => rising.insert(position),
+                (true, false) => falling.insert(position),
+                _ => false,
+            };
+        }
+    }
+    (rising, falling)
+}
+
+fn shortest_run(lines: &[Vec<bool>]) -> Option<usize> {
+    lines.iter()
+        .flat_map(|line| line.chunk_by(|a, b| a == b).filter(|run| run[0]).map(|run| run.len()))
+        .min()
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+// Decodes a PNG into its size, the brightness of each pixel and whether each pixel is opaque enough to count.
+fn decode_png(bytes: &[u8]) -> Result<(usize, usize, Vec<u8>, Vec<bool>), PatternError> {
+    let image_error = |error: png::DecodingError| PatternError::Image(error.to_string());
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(image_error)?;
+    check_image_size(reader.info().width as usize, reader.info().height as usize)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(image_error)?;
+    buffer.truncate(info.buffer_size());
+
+    let samples = info.color_type.samples();
+    let pixels = buffer.chunks_exact(samples);
+    let (brightness, opaque) = match info.color_type {
+        png::ColorType::Grayscale => pixels.map(|pixel| (pixel[0], true)).unzip(),
+        png::ColorType::GrayscaleAlpha => pixels.map(|pixel| (pixel[0], pixel[1] >= 128)).unzip(),
+        png::ColorType::Rgb => pixels.map(|pixel| (luma(pixel), true)).unzip(),
+        png::ColorType::Rgba => pixels.map(|pixel| (luma(pixel), pixel[3] >= 128)).unzip(),
+        png::ColorType::Indexed => return Err(PatternError::Image("Unexpected indexed PNG".to_string())),
+    };
+    Ok((info.width as usize, info.height as usize, brightness, opaque))
+}
+
+// Decodes a plain or binary PPM (P3, P6) or PGM (P2, P5) image.
+fn decode_netpbm(bytes: &[u8]) -> Result<(usize, usize, Vec<u8>, Vec<bool>), PatternError> {
+    let invalid = |message: &str| PatternError::Image(format!("Invalid PPM image: {}", message));
+    let magic = &bytes[..2];
+    let samples = if magic == b"P3" || magic == b"P6" { 3 } else { 1 };
+    let binary = magic == b"P6" || magic == b"P5";
+
+    // The header is the magic number then the width, height and maximum value, separated by whitespace or comments.
+    let mut position = 2;
+    let mut header = [0usize; 3];
+    for value in header.iter_mut() {
+        *value = next_number(bytes, &mut position).ok_or_else(|| invalid("truncated header"))?;
+    }
+    let [width, height, max_value] = header;
+    if max_value == 0 || max_value > 65535 {
+        return Err(invalid("the maximum value must be between 1 and 65535"));
+    }
+
+    let count = check_image_size(width, height)? * samples;
+    let values: Vec<usize> = if binary {
+        // A single whitespace character separates the header from the pixels.
+        let data = &bytes[(position + 1).min(bytes.len())..];
+        let sample_bytes = if max_value < 256 { 1 } else { 2 };
+        if data.len() < count * sample_bytes {
+            return Err(invalid("truncated pixel data"));
+        }
+        data.chunks_exact(sample_bytes).take(count)
+            .map(|sample| sample.iter().fold(0, |value, &byte| value * 256 + byte as usize))
+            .collect()
+    } else {
+        (0..count).map(|_| next_number(bytes, &mut position)).collect::<Option<_>>().ok_or_else(|| invalid("truncated pixel data"))?
+    };
+
+    let scaled: Vec<u8> = values.iter().map(|&value| (value.min(max_value) * 255 / max_value) as u8).collect();
+    let brightness = if samples == 3 { scaled.chunks_exact(3).map(luma).collect() } else { scaled };
+    Ok((width, height, brightness, vec![true; width * height]))
+}
+
+// Returns the number of pixels of an image, or an error when there are more than the import accepts.
+fn check_image_size(width: usize, height: usize) -> Result<usize, PatternError> {
+    width.checked_mul(height).filter(|&pixels| pixels <= MAX_IMAGE_PIXELS).ok_or_else(|| {
+        PatternError::Image(format!("The image is {}x{} pixels, more than the {} pixels that can be imported", width, height, MAX_IMAGE_PIXELS))
+    })
+}
+
+// Reads the next decimal number of a Netpbm header, skipping whitespace and comments, and leaves `position` after it.
+fn next_number(bytes: &[u8], position: &mut usize) -> Option<usize> {
+    loop {
+        match bytes.get(*position)? {
+            byte if byte.is_ascii_whitespace() => *position += 1,
+            b'#' => {
+                while bytes.get(*position).is_some_and(|&byte| byte != b'\n') {
+                    *position += 1;
+                }
+            }
+            _ => break,
+        }
+    }
+    let start = *position;
+    while bytes.get(*position).is_some_and(u8::is_ascii_digit) {
+        *position += 1;
+    }
+    std::str::from_utf8(&bytes[start..*position]).ok()?.parse().ok()
+}
+
+fn luma(pixel: &[u8]) -> u8 {
+    ((pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) / 1000) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::pattern_io::parse_pattern;
+
+    // Writes a binary PGM where each cell of the plaintext pattern is a `cell_size` square of black or white
+    // pixels, followed by a one pixel grey grid line when `grid` is set, after a white margin.
+    fn screenshot(text: &str, cell_size: usize, grid: bool, margin: usize) -> Vec<u8> {
+        let pattern = parse_pattern(text).unwrap();
+        let pitch = cell_size + grid as usize;
+        let (width, height) = (2 * margin + pattern.width * pitch, 2 * margin + pattern.height * pitch);
+        let mut pixels = vec![255u8; width * height];
+        for y in 0..height - 2 * margin {
+            for x in 0..width - 2 * margin {
+                let on_grid = grid && (x % pitch == cell_size || y % pitch == cell_size);
+                let alive = pattern.get_cell(x / pitch, y / pitch) == CellState::Alive;
+                pixels[(y + margin) * width + x + margin] = if on_grid { 200 } else if alive { 0 } else { 255 };
+            }
+        }
+        let mut bytes = format!("P5\n# test\n{} {}\n255\n", width, height).into_bytes();
+        bytes.extend(pixels);
+        bytes
+    }
+
+    const GLIDER: &str = " X\n  X\nXXX\n";
+
+    #[test]
+    fn test_pixel_per_cell() {
+        let pattern = parse_image_pattern(&screenshot(GLIDER, 1, false, 3), &ImageImportOptions::default()).unwrap();
+        assert_eq!(pattern, parse_pattern(GLIDER).unwrap());
+    }
+
+    #[test]
+    fn test_detect_grid() {
+        let options = ImageImportOptions { cell_size: None, ..ImageImportOptions::default() };
+        let glider = parse_pattern(GLIDER).unwrap();
+        assert_eq!(parse_image_pattern(&screenshot(GLIDER, 5, false, 2), &options).unwrap(), glider);
+        // The grey grid lines are lighter than the threshold, so they count as dead pixels.
+        assert_eq!(parse_image_pattern(&screenshot(GLIDER, 6, true, 7), &options).unwrap(), glider);
+
+        let block = parse_image_pattern(&screenshot("XX\nXX\n", 4, false, 1), &options).unwrap();
+        assert_eq!((block.width, block.height), (1, 1));
+    }
+
+    #[test]
+    fn test_fixed_cell_size_and_invert() {
+        let options = ImageImportOptions { cell_size: Some(3), invert: true, threshold: 100 };
+        let image = screenshot("X.X\n", 3, false, 0);
+        let pattern = parse_image_pattern(&image, &options).unwrap();
+        assert_eq!(pattern, parse_pattern("X\n").unwrap());
+    }
+
+    #[test]
+    fn test_png_and_ppm() {
+        let mut png_bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png_bytes, 3, 1);
+        encoder.set_color(png::ColorType::Rgba);
+        let mut writer = encoder.write_header().unwrap();
+        // Black, transparent black and white pixels.
+        writer.write_image_data(&[0, 0, 0, 255, 0, 0, 0, 0, 255, 255, 255, 255]).unwrap();
+        writer.finish().unwrap();
+        let pattern = parse_image_pattern(&png_bytes, &ImageImportOptions::default()).unwrap();
+        assert_eq!(pattern, parse_pattern("X\n").unwrap());
+
+        let ppm = b"P3 3 1 15\n0 0 0  15 15 15  1 2 0\n";
+        let pattern = parse_image_pattern(ppm, &ImageImportOptions::default()).unwrap();
+        assert_eq!(pattern, parse_pattern("X.X\n").unwrap());
+    }
+
+    #[test]
+    fn test_invalid_images() {
+        assert!(matches!(parse_image_pattern(b"GIF89a", &ImageImportOptions::default()), Err(PatternError::Image(_))));
+        assert!(parse_image_pattern(b"P6 2 2 255\n\x00", &ImageImportOptions::default()).is_err());
+        assert!(parse_image_pattern(b"P2 1 1 255\n255\n", &ImageImportOptions::default()).unwrap_err().to_string().contains("no live cell"));
+        let huge = parse_image_pattern(b"P5 3000000000 3000000000 255\n\x00", &ImageImportOptions::default());
+        assert!(matches!(huge, Err(PatternError::Image(message)) if message.contains("more than")));
+        assert!(matches!(parse_image_pattern(b"P5 5000 5000 255\n\x00", &ImageImportOptions::default()), Err(PatternError::Image(_))));
+        assert!(is_image_path("shot.PNG") && !is_image_path("glider.rle"));
+    }
+}
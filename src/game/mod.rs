@@ -1,5 +1,30 @@
+/// The state of a game.
 pub mod game_state;
+/// The board and its evolution.
 pub mod board;
-pub mod cell;
+/// The state of a cell.
 pub mod cell_state;
-mod rules;
\ No newline at end of file
+/// Separating the objects of a board and counting them.
+pub mod census;
+/// How patterns are combined with the board.
+pub mod paste_mode;
+/// Patterns imported from images.
+pub mod image_import;
+/// Patterns of cells.
+pub mod pattern;
+/// Errors of the pattern loading pipeline.
+pub mod pattern_error;
+/// Reading and parsing pattern files.
+pub mod pattern_io;
+/// Where and how patterns are placed.
+pub mod placement;
+/// The rules of the game.
+pub mod rules;
+/// Starting states encoded in links.
+pub mod share;
+/// Detection of boards that stopped evolving.
+pub mod stability;
+/// What lies beyond the edges of the board.
+pub mod topology;
+/// Rotations and reflections of patterns.
+pub mod transform;
@@ -0,0 +1,60 @@
+use crate::game::cell_state::CellState;
+
+use std::str::FromStr;
+
+/// How the cells of a pasted pattern are combined with the cells already on the board.
+#[derive(Clone, PartialEq, Debug, Copy)]
+pub enum PasteMode {
+    /// Live pattern cells are added, board cells are kept.
+    Or,
+    /// Live pattern cells toggle the board cells.
+    Xor,
+    /// The pattern cells, dead or alive, replace the board cells.
+    Copy,
+}
+
+impl PasteMode {
+    /// Returns the state of a board cell after pasting a pattern cell on it.
+    pub fn apply(&self, board_cell: CellState, pattern_cell: CellState) -> CellState {
+        match (self, board_cell, pattern_cell) {
+            (PasteMode::Copy, _, pattern_cell) => pattern_cell,
+            (PasteMode::Or, CellState::Dead, CellState::Dead) => CellState::Dead,
+            (PasteMode::Or, _, _) => CellState::Alive,
+            (PasteMode::Xor, board_cell, pattern_cell) if board_cell == pattern_cell => CellState::Dead,
+            (PasteMode::Xor, _, _) => CellState::Alive,
+        }
+    }
+}
+
+impl FromStr for PasteMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "or" => Ok(PasteMode::Or),
+            "xor" => Ok(PasteMode::Xor),
+            "copy" => Ok(PasteMode::Copy),
+            _ => Err(format!("Unknown paste mode '{}', expected one of: or, xor, copy", s)),
+        }
+    }
+}
+
+#[test]
+fn or_keeps_board_cells() {
+    assert_eq!(PasteMode::Or.apply(CellState::Alive, CellState::Dead), CellState::Alive);
+    assert_eq!(PasteMode::Or.apply(CellState::Dead, CellState::Alive), CellState::Alive);
+    assert_eq!(PasteMode::Or.apply(CellState::Dead, CellState::Dead), CellState::Dead);
+}
+
+#[test]
+fn xor_toggles_board_cells() {
+    assert_eq!(PasteMode::Xor.apply(CellState::Alive, CellState::Alive), CellState::Dead);
+    assert_eq!(PasteMode::Xor.apply(CellState::Alive, CellState::Dead), CellState::Alive);
+    assert_eq!(PasteMode::Xor.apply(CellState::Dead, CellState::Alive), CellState::Alive);
+}
+
+#[test]
+fn copy_overwrites_board_cells() {
+    assert_eq!(PasteMode::Copy.apply(CellState::Alive, CellState::Dead), CellState::Dead);
+    assert_eq!(PasteMode::Copy.apply(CellState::Dead, CellState::Alive), CellState::Alive);
+}
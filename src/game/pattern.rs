@@ -0,0 +1,91 @@
+use crate::game::cell_state::CellState;
+use crate::game::rules::Rule;
+use crate::game::transform::Transform;
+
+/// A rectangular pattern of cells that can be placed on a [`Board`](crate::Board).
+#[derive(Clone, PartialEq, Debug)]
+pub struct Pattern {
+    /// Number of columns.
+    pub width: usize,
+    /// Number of rows.
+    pub height: usize,
+    /// Cell states indexed as `cells[x][y]`.
+    pub cells: Vec<Vec<CellState>>,
+    /// The rule the pattern was designed for, when its file declares one.
+    pub rule: Option<Rule>,
+}
+
+impl Pattern {
+    /// Creates a pattern with all its cells dead.
+    pub fn new(width: usize, height: usize) -> Pattern {
+        let cells = vec![vec![CellState::Dead; height]; width];
+        Pattern { width, height, cells, rule: None }
+    }
+
+    /// Returns the state of a cell, dead outside the pattern.
+    pub fn get_cell(&self, x: usize, y: usize) -> CellState {
+        if x < self.width && y < self.height {
+            return self.cells[x][y];
+        }
+        CellState::Dead
+    }
+
+    /// Sets the state of a cell, ignoring coordinates outside the pattern.
+    pub fn set_cell(&mut self, x: usize, y: usize, cell_state: CellState) {
+        if x < self.width && y < self.height {
+            self.cells[x][y] = cell_state;
+        }
+    }
+
+    /// Returns a copy of the pattern rotated or reflected by `transform`.
+    pub fn transformed(&self, transform: Transform) -> Pattern {
+        let (width, height) = transform.transformed_size(self.width, self.height);
+        let mut pattern = Pattern::new(width, height);
+        pattern.rule = self.rule;
+        for (x, column) in self.cells.iter().enumerate() {
+            for (y, cell) in column.iter().enumerate() {
+                let (new_x, new_y) = transform.apply(x, y, self.width, self.height);
+                pattern.cells[new_x][new_y] = *cell;
+            }
+        }
+        pattern
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glider() -> Pattern {
+        let mut pattern = Pattern::new(3, 3);
+        for (x, y) in [(2, 0), (0, 1), (2, 1), (1, 2), (2, 2)] {
+            pattern.set_cell(x, y, CellState::Alive);
+        }
+        pattern
+    }
+
+    #[test]
+    fn test_transformed() {
+        // X X X      X
+        //        ->  X
+        //            X
+        let mut pattern = Pattern::new(3, 1);
+        for x in 0..3 {
+            pattern.set_cell(x, 0, CellState::Alive);
+        }
+        let pattern = pattern.transformed(Transform::Rotate90);
+        assert_eq!(pattern.width, 1);
+        assert_eq!(pattern.height, 3);
+        assert!(pattern.cells[0].iter().all(|cell| *cell == CellState::Alive));
+    }
+
+    #[test]
+    fn test_rotating_four_times_is_identity() {
+        let pattern = glider();
+        let mut rotated = pattern.clone();
+        for _ in 0..4 {
+            rotated = rotated.transformed(Transform::Rotate90);
+        }
+        assert_eq!(rotated, pattern);
+    }
+}
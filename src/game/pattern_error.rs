@@ -0,0 +1,79 @@
+use std::fmt;
+use std::io;
+
+/// Errors raised while loading a pattern and placing it on a board.
+#[derive(Debug)]
+pub enum PatternError {
+    /// The pattern file could not be read.
+    Io {
+        /// Path of the pattern file.
+        path: String,
+        /// The underlying I/O error.
+        source: io::Error,
+    },
+    /// The pattern text is malformed. Lines and columns start at 1.
+    Parse {
+        /// Line of the error.
+        line: usize,
+        /// Column of the error.
+        column: usize,
+        /// What is wrong at that position.
+        message: String,
+    },
+    /// The pattern does not fit in the board at the requested position.
+    PatternTooLarge {
+        /// Width of the pattern, once transformed.
+        pattern_width: usize,
+        /// Height of the pattern, once transformed.
+        pattern_height: usize,
+        /// Requested column of the top-left corner.
+        x: usize,
+        /// Requested row of the top-left corner.
+        y: usize,
+        /// Width of the board.
+        board_width: usize,
+        /// Height of the board.
+        board_height: usize,
+    },
+    /// The pattern declares a rule this crate cannot simulate.
+    UnsupportedRule(String),
+    /// The image a pattern is imported from cannot be decoded or holds no live cell.
+    Image(String),
+}
+
+impl PatternError {
+    /// Shorthand for a [`PatternError::Parse`] error.
+    pub fn parse(line: usize, column: usize, message: &str) -> PatternError {
+        PatternError::Parse { line, column, message: message.to_string() }
+    }
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::Io { path, source } => {
+                write!(f, "Cannot read pattern file '{}': {}", path, source)
+            }
+            PatternError::Parse { line, column, message } => {
+                write!(f, "Invalid pattern at line {}, column {}: {}", line, column, message)
+            }
+            PatternError::PatternTooLarge { pattern_width, pattern_height, x, y, board_width, board_height } => {
+                write!(f, "Pattern of {}x{} cells placed at ({}, {}) does not fit in the {}x{} board",
+                       pattern_width, pattern_height, x, y, board_width, board_height)
+            }
+            PatternError::UnsupportedRule(rule) => {
+                write!(f, "Unsupported rule '{}', only Life-like rules such as B3/S23 are supported", rule)
+            }
+            PatternError::Image(message) => write!(f, "Cannot import the image: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PatternError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,420 @@
+use crate::game::cell_state::CellState;
+use crate::game::pattern::Pattern;
+use crate::game::pattern_error::PatternError;
+use crate::game::rules::Rule;
+
+use std::fs;
+use std::str::FromStr;
+
+const RLE_LINE_LENGTH: usize = 70;
+
+/// The widest and highest pattern that can be parsed, in cells.
+pub const MAX_PATTERN_SIZE: usize = 100_000;
+/// The most cells, dead or alive, that a parsed pattern can hold.
+pub const MAX_PATTERN_CELLS: usize = 1 << 26;
+
+/// The pattern file formats that can be written.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PatternFormat {
+    /// Run length encoded, with the rule in the header.
+    Rle,
+    /// The `.cells` plaintext format, `O` for alive and `.` for dead cells.
+    Cells,
+    /// The plaintext format of `gh-pages/patterns`, `X` for alive and spaces for dead cells.
+    Text,
+}
+
+impl FromStr for PatternFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "rle" => Ok(PatternFormat::Rle),
+            "cells" => Ok(PatternFormat::Cells),
+            "txt" => Ok(PatternFormat::Text),
+            _ => Err(format!("Unknown pattern format '{}', expected one of: rle, cells, txt", s)),
+        }
+    }
+}
+
+/// Reads and parses a pattern file, see [`parse_pattern`].
+pub fn read_pattern_file(path: &str) -> Result<Pattern, PatternError> {
+    let text = fs::read_to_string(path).map_err(|source| PatternError::Io { path: path.to_string(), source })?;
+    parse_pattern(&text)
+}
+
+/// Parses a pattern in RLE or in one of the plaintext formats, detecting the format from the text.
+pub fn parse_pattern(text: &str) -> Result<Pattern, PatternError> {
+    if is_rle(text) {
+        parse_rle(text)
+    } else {
+        parse_plaintext(text)
+    }
+}
+
+fn is_rle(text: &str) -> bool {
+    text.lines()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .is_some_and(|line| line.starts_with('x') && line[1..].trim_start().starts_with('='))
+}
+
+/// Writes a pattern in the given format.
+pub fn write_pattern(pattern: &Pattern, format: PatternFormat) -> String {
+    match format {
+        PatternFormat::Rle => write_rle(pattern),
+        PatternFormat::Cells => write_plaintext(pattern, 'O', '.'),
+        PatternFormat::Text => write_plaintext(pattern, 'X', ' '),
+    }
+}
+
+fn write_plaintext(pattern: &Pattern, alive: char, dead: char) -> String {
+    let mut text = String::new();
+    for y in 0..pattern.height {
+        for x in 0..pattern.width {
+            text.push(if pattern.get_cell(x, y) == CellState::Alive { alive } else { dead });
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Writes a pattern in the run length encoded (RLE) format.
+pub fn write_rle(pattern: &Pattern) -> String {
+    let mut tokens = Vec::new();
+    let mut pending_rows = 0;
+    for y in 0..pattern.height {
+        let row: Vec<CellState> = (0..pattern.width).map(|x| pattern.get_cell(x, y)).collect();
+        let row_length = row.iter().rposition(|cell| *cell == CellState::Alive).map_or(0, |x| x + 1);
+        if row_length == 0 {
+            pending_rows += 1;
+            continue;
+        }
+
+        if pending_rows > 0 {
+            tokens.push(rle_run(pending_rows, '$'));
+        }
+        let mut x = 0;
+        while x < row_length {
+            let run_length = row[x..row_length].iter().take_while(|cell| **cell == row[x]).count();
+            tokens.push(rle_run(run_length, if row[x] == CellState::Alive { 'o' } else { 'b' }));
+            x += run_length;
+        }
+        pending_rows = 1;
+    }
+    tokens.push("!".to_string());
+
+    let rule = pattern.rule.unwrap_or_default();
+    let mut text = format!("x = {}, y = {}, rule = {}\n", pattern.width, pattern.height, rule);
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length + token.len() > RLE_LINE_LENGTH {
+            text.push('\n');
+            line_length = 0;
+        }
+        line_length += token.len();
+        text.push_str(&token);
+    }
+    text.push('\n');
+    text
+}
+
+fn rle_run(length: usize, tag: char) -> String {
+    if length == 1 {
+        tag.to_string()
+    } else {
+        format!("{}{}", length, tag)
+    }
+}
+
+/// Parses a plaintext pattern, either in the `X` format of `gh-pages/patterns` or in the `.cells`
+/// format where `O` is alive, `.` is dead and lines starting with `!` are comments.
+pub fn parse_plaintext(text: &str) -> Result<Pattern, PatternError> {
+    let mut rows: Vec<Vec<CellState>> = Vec::new();
+    for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with('!') {
+            continue;
+        }
+
+        let mut row = Vec::new();
+        for (column_index, char) in line.chars().enumerate() {
+            let cell = match char {
+                'X' | 'O' | '*' => CellState::Alive,
+                '.' | ' ' => CellState::Dead,
+                _ => {
+                    return Err(PatternError::parse(line_index + 1, column_index + 1,
+                                                   &format!("unexpected character '{}'", char)));
+                }
+            };
+            row.push(cell);
+        }
+        rows.push(row);
+    }
+
+    while rows.last().is_some_and(|row| row.is_empty()) {
+        rows.pop();
+    }
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    if width == 0 {
+        return Err(PatternError::parse(1, 1, "the pattern has no cells"));
+    }
+    check_pattern_size(width, rows.len(), 1)?;
+
+    let mut pattern = Pattern::new(width, rows.len());
+    for (y, row) in rows.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            pattern.set_cell(x, y, *cell);
+        }
+    }
+    Ok(pattern)
+}
+
+/// Parses a pattern in the run length encoded (RLE) format.
+pub fn parse_rle(text: &str) -> Result<Pattern, PatternError> {
+    let mut header: Option<(usize, usize, Option<Rule>)> = None;
+    let mut header_line = 1;
+    let mut alive_cells = Vec::new();
+    let (mut x, mut y): (usize, usize) = (0, 0);
+    let mut run_count: Option<usize> = None;
+
+    'lines: for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with('#') || (header.is_none() && line.trim().is_empty()) {
+            continue;
+        }
+        if header.is_none() {
+            header = Some(parse_rle_header(line, line_index + 1)?);
+            header_line = line_index + 1;
+            continue;
+        }
+
+        for (column_index, char) in line.chars().enumerate() {
+            match char {
+                '0'..='9' => {
+                    let digit = char.to_digit(10).unwrap() as usize;
+                    run_count = run_count.unwrap_or(0).checked_mul(10).and_then(|count| count.checked_add(digit));
+                    if run_count.is_none() {
+                        return Err(PatternError::parse(line_index + 1, column_index + 1, "run count is too large"));
+                    }
+                }
+                'b' | 'o' => {
+                    let count = run_count.take().unwrap_or(1);
+                    let too_large = || PatternError::parse(line_index + 1, column_index + 1, &too_large_message());
+                    let end = x.checked_add(count).filter(|&end| end <= MAX_PATTERN_SIZE).ok_or_else(too_large)?;
+                    if char == 'o' {
+                        if alive_cells.len() + count > MAX_PATTERN_CELLS {
+                            return Err(too_large());
+                        }
+                        alive_cells.extend((x..end).map(|cell_x| (cell_x, y)));
+                    }
+                    x = end;
+                }
+                '$' => {
+                    y = y.checked_add(run_count.take().unwrap_or(1)).filter(|&y| y <= MAX_PATTERN_SIZE)
+                        .ok_or_else(|| PatternError::parse(line_index + 1, column_index + 1, &too_large_message()))?;
+                    x = 0;
+                }
+                '!' => break 'lines,
+                _ if char.is_whitespace() => {}
+                _ => {
+                    return Err(PatternError::parse(line_index + 1, column_index + 1,
+                                                   &format!("unexpected character '{}'", char)));
+                }
+            }
+        }
+    }
+
+    let (header_width, header_height, rule) = header.ok_or_else(|| PatternError::parse(1, 1, "missing the 'x = ..., y = ...' header"))?;
+    let width = alive_cells.iter().map(|(x, _)| x + 1).fold(header_width, usize::max);
+    let height = alive_cells.iter().map(|(_, y)| y + 1).fold(header_height, usize::max);
+    check_pattern_size(width, height, header_line)?;
+
+    let mut pattern = Pattern::new(width, height);
+    pattern.rule = rule;
+    for (x, y) in alive_cells {
+        pattern.set_cell(x, y, CellState::Alive);
+    }
+    Ok(pattern)
+}
+
+fn parse_rle_header(line: &str, line_number: usize) -> Result<(usize, usize, Option<Rule>), PatternError> {
+    let mut width = None;
+    let mut height = None;
+    let mut rule = None;
+
+    let mut column = 1;
+    for field in line.split(',') {
+        let (key, value) = field.split_once('=')
+            .ok_or_else(|| PatternError::parse(line_number, column, "expected 'key = value' in the header"))?;
+        let value = value.trim();
+        match key.trim() {
+            "x" => width = Some(parse_dimension(value, line_number, column)?),
+            "y" => height = Some(parse_dimension(value, line_number, column)?),
+            "rule" => rule = Some(value.parse().map_err(|_| PatternError::UnsupportedRule(value.to_string()))?),
+            _ => {}
+        }
+        column += field.chars().count() + 1;
+    }
+
+    match (width, height) {
+        (Some(width), Some(height)) => {
+            check_pattern_size(width, height, line_number)?;
+            Ok((width, height, rule))
+        }
+        _ => Err(PatternError::parse(line_number, 1, "the header must define both x and y")),
+    }
+}
+
+// Patterns come from files, drops and links, so their size is checked before any cell is allocated.
+fn check_pattern_size(width: usize, height: usize, line_number: usize) -> Result<(), PatternError> {
+    let cells = width.checked_mul(height);
+    if width > MAX_PATTERN_SIZE || height > MAX_PATTERN_SIZE || cells.is_none_or(|cells| cells > MAX_PATTERN_CELLS) {
+        return Err(PatternError::parse(line_number, 1, &format!("the pattern of {}x{} cells is too large, {}", width, height, too_large_message())));
+    }
+    Ok(())
+}
+
+fn too_large_message() -> String {
+    format!("patterns are limited to {} cells wide and high and {} cells in all", MAX_PATTERN_SIZE, MAX_PATTERN_CELLS)
+}
+
+fn parse_dimension(value: &str, line_number: usize, column: usize) -> Result<usize, PatternError> {
+    value.parse().map_err(|_| PatternError::parse(line_number, column, &format!("invalid dimension '{}'", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alive_cells(pattern: &Pattern) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for y in 0..pattern.height {
+            for x in 0..pattern.width {
+                if pattern.get_cell(x, y) == CellState::Alive {
+                    cells.push((x, y));
+                }
+            }
+        }
+        cells
+    }
+
+    const GLIDER: [(usize, usize); 5] = [(2, 0), (0, 1), (2, 1), (1, 2), (2, 2)];
+
+    #[test]
+    fn test_parse_x_format() {
+        let pattern = parse_pattern("  X\nX X\n XX\n").unwrap();
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(alive_cells(&pattern), GLIDER);
+    }
+
+    #[test]
+    fn test_parse_cells_format() {
+        let pattern = parse_pattern("!Name: Glider\n..O\nO.O\n.OO\n").unwrap();
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(alive_cells(&pattern), GLIDER);
+    }
+
+    #[test]
+    fn test_parse_uses_longest_row() {
+        let pattern = parse_pattern("X\nX  X").unwrap();
+        assert_eq!(pattern.width, 4);
+        assert_eq!(pattern.get_cell(3, 1), CellState::Alive);
+    }
+
+    #[test]
+    fn test_parse_rle() {
+        let pattern = parse_pattern("#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n").unwrap();
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!(pattern.rule, Some(Rule::conway()));
+        assert_eq!(alive_cells(&pattern), vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn test_parse_rle_multiline_with_blank_rows() {
+        let pattern = parse_pattern("x = 2, y = 4\n2o2$\no!").unwrap();
+        assert_eq!((pattern.width, pattern.height), (2, 4));
+        assert_eq!(alive_cells(&pattern), vec![(0, 0), (1, 0), (0, 2)]);
+    }
+
+    #[test]
+    fn test_empty_pattern() {
+        assert!(matches!(parse_pattern(""), Err(PatternError::Parse { line: 1, column: 1, .. })));
+        assert!(matches!(parse_pattern("\n\n"), Err(PatternError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_plaintext_error_position() {
+        let error = parse_pattern(" X\nX?X\n").unwrap_err();
+        assert!(matches!(error, PatternError::Parse { line: 2, column: 2, .. }), "{}", error);
+    }
+
+    #[test]
+    fn test_rle_error_position() {
+        let error = parse_pattern("x = 3, y = 3\nbo$2bq!\n").unwrap_err();
+        assert!(matches!(error, PatternError::Parse { line: 2, column: 6, .. }), "{}", error);
+    }
+
+    #[test]
+    fn test_rle_invalid_header() {
+        let error = parse_pattern("x = 3, y = three\nbo!\n").unwrap_err();
+        assert!(matches!(error, PatternError::Parse { line: 1, column: 7, .. }), "{}", error);
+    }
+
+    #[test]
+    fn test_oversized_patterns_are_rejected() {
+        for text in ["x = 3000000000, y = 3000000000\no!\n", "x = 100000, y = 100000\no!\n", "x = 1, y = 1\n99999999999o!\n",
+                     "x = 1, y = 1\no99999999$o!\n"] {
+            let error = parse_pattern(text).unwrap_err();
+            assert!(matches!(error, PatternError::Parse { .. }), "{}", error);
+        }
+        let error = parse_pattern("x = 1, y = 1\nbo$18446744073709551615o!\n").unwrap_err();
+        assert!(matches!(error, PatternError::Parse { line: 2, column: 24, .. }), "{}", error);
+
+        let wide = format!("{}X\n", " ".repeat(MAX_PATTERN_SIZE));
+        assert!(parse_pattern(&wide).is_err());
+        assert_eq!(parse_pattern("x = 100000, y = 1\no!\n").unwrap().width, MAX_PATTERN_SIZE);
+    }
+
+    #[test]
+    fn test_rle_unsupported_rule() {
+        let pattern = parse_pattern("x = 1, y = 1, rule = B36/S23\no!\n").unwrap();
+        assert_eq!(pattern.rule, Some(Rule::new(&[3, 6], &[2, 3])));
+
+        let error = parse_pattern("x = 1, y = 1, rule = 23/3/3\no!\n").unwrap_err();
+        assert!(matches!(error, PatternError::UnsupportedRule(ref rule) if rule == "23/3/3"));
+    }
+
+    #[test]
+    fn test_write_rle() {
+        let pattern = parse_pattern("  X\nX X\n XX\n").unwrap();
+        assert_eq!(write_rle(&pattern), "x = 3, y = 3, rule = B3/S23\n2bo$obo$b2o!\n");
+    }
+
+    #[test]
+    fn test_write_rle_with_empty_rows() {
+        let pattern = parse_pattern("XX\n\n\nX\n").unwrap();
+        assert_eq!(write_rle(&pattern), "x = 2, y = 4, rule = B3/S23\n2o3$o!\n");
+    }
+
+    #[test]
+    fn test_write_rle_wraps_lines() {
+        let row: String = "X ".repeat(100);
+        let rle = write_rle(&parse_pattern(&row).unwrap());
+        assert!(rle.lines().all(|line| line.len() <= RLE_LINE_LENGTH));
+        assert_eq!(parse_pattern(&rle).unwrap().cells, parse_pattern(&row).unwrap().cells);
+    }
+
+    #[test]
+    fn test_write_plaintext_round_trip() {
+        let pattern = parse_pattern("  X\nX X\n XX\n").unwrap();
+        assert_eq!(write_pattern(&pattern, PatternFormat::Cells), "..O\nO.O\n.OO\n");
+        assert_eq!(parse_pattern(&write_pattern(&pattern, PatternFormat::Text)).unwrap(), pattern);
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let error = read_pattern_file("does/not/exist.rle").unwrap_err();
+        assert!(matches!(error, PatternError::Io { .. }));
+    }
+}
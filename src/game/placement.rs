@@ -0,0 +1,23 @@
+use crate::game::paste_mode::PasteMode;
+use crate::game::transform::Transform;
+
+/// Where and how a pattern is placed on a board.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Placement {
+    /// Top-left corner of the transformed pattern, or `None` to centre it.
+    pub position: Option<(usize, usize)>,
+    /// Rotation or reflection applied to the pattern.
+    pub transform: Transform,
+    /// How the pattern is combined with the board.
+    pub paste_mode: PasteMode,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Placement {
+            position: None,
+            transform: Transform::Identity,
+            paste_mode: PasteMode::Or,
+        }
+    }
+}
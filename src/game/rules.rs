@@ -1,5 +1,89 @@
 use crate::game::cell_state::CellState;
 
+use std::fmt;
+use std::str::FromStr;
+
+/// A Life-like rule: the numbers of live neighbours for which a dead cell is born and a live cell survives.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rule {
+    birth: [bool; 9],
+    survival: [bool; 9],
+}
+
+impl Rule {
+    /// Creates a rule from its birth and survival neighbour counts, counts above 8 are ignored.
+    pub fn new(birth: &[u8], survival: &[u8]) -> Rule {
+        let mut rule = Rule { birth: [false; 9], survival: [false; 9] };
+        for &count in birth.iter().filter(|&&count| count <= 8) {
+            rule.birth[count as usize] = true;
+        }
+        for &count in survival.iter().filter(|&&count| count <= 8) {
+            rule.survival[count as usize] = true;
+        }
+        rule
+    }
+
+    /// Conway's Game of Life, B3/S23.
+    pub fn conway() -> Rule {
+        Rule::new(&[3], &[2, 3])
+    }
+
+    /// Returns the next state of a cell with `alive_neighbors` live neighbours.
+    pub fn evolve(&self, current_state: CellState, alive_neighbors: u8) -> CellState {
+        let counts = match current_state {
+            CellState::Alive => &self.survival,
+            CellState::Dead => &self.birth,
+        };
+        match counts.get(alive_neighbors as usize) {
+            Some(true) => CellState::Alive,
+            _ => CellState::Dead,
+        }
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::conway()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |counts: &[bool; 9]| -> String {
+            (0..9).filter(|&count| counts[count]).map(|count| count.to_string()).collect()
+        };
+        write!(f, "B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
+
+// Accepts the B/S notation ("B3/S23", in any order and case) and the S/B notation ("23/3").
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid rule '{}', expected a Life-like rule such as B3/S23", s);
+        let parse_counts = |digits: &str| -> Result<Vec<u8>, String> {
+            digits.chars()
+                .map(|digit| digit.to_digit(9).map(|count| count as u8).ok_or_else(invalid))
+                .collect()
+        };
+
+        let (first, second) = s.trim().split_once('/').ok_or_else(invalid)?;
+        let (first, second) = (first.to_ascii_uppercase(), second.to_ascii_uppercase());
+        let (birth, survival) = match (first.strip_prefix('B'), second.strip_prefix('S')) {
+            (Some(birth), Some(survival)) => (birth.to_string(), survival.to_string()),
+            _ => match (first.strip_prefix('S'), second.strip_prefix('B')) {
+                (Some(survival), Some(birth)) => (birth.to_string(), survival.to_string()),
+                _ if !first.starts_with(['B', 'S']) && !second.starts_with(['B', 'S']) => (second, first),
+                _ => return Err(invalid()),
+            },
+        };
+
+        Ok(Rule::new(&parse_counts(&birth)?, &parse_counts(&survival)?))
+    }
+}
+
+/// Returns the next state of a cell under Conway's rules (B3/S23).
 pub fn evolve_cell(current_state: CellState, alive_neighbors: u8) -> CellState {
     match (current_state, alive_neighbors) {
         (CellState::Dead, 3) => CellState::Alive,
@@ -30,4 +114,37 @@ fn cell_dies() {
 fn cell_stays_dead() {
     assert_eq!(evolve_cell(CellState::Dead, 2), CellState::Dead, "A dead cell with fewer than 3 live neighbors should stay dead.");
     assert_eq!(evolve_cell(CellState::Dead, 4), CellState::Dead, "A dead cell with more than 3 live neighbors should stay dead.");
-}
\ No newline at end of file
+}
+#[test]
+fn conway_rule_matches_evolve_cell() {
+    let rule = Rule::conway();
+    for alive_neighbors in 0..=8 {
+        for state in [CellState::Alive, CellState::Dead] {
+            assert_eq!(rule.evolve(state, alive_neighbors), evolve_cell(state, alive_neighbors));
+        }
+    }
+}
+
+#[test]
+fn parse_rule_notations() {
+    let highlife = Rule::new(&[3, 6], &[2, 3]);
+    assert_eq!("B36/S23".parse::<Rule>(), Ok(highlife));
+    assert_eq!("b36/s23".parse::<Rule>(), Ok(highlife));
+    assert_eq!("S23/B36".parse::<Rule>(), Ok(highlife));
+    assert_eq!("23/36".parse::<Rule>(), Ok(highlife));
+    assert_eq!("B2/S".parse::<Rule>(), Ok(Rule::new(&[2], &[])));
+}
+
+#[test]
+fn parse_invalid_rules() {
+    assert!("B39/S23".parse::<Rule>().is_err());
+    assert!("B3S23".parse::<Rule>().is_err());
+    assert!("B3/23".parse::<Rule>().is_err());
+    assert!("R3,C2,M1".parse::<Rule>().is_err());
+}
+
+#[test]
+fn rule_display() {
+    assert_eq!(Rule::conway().to_string(), "B3/S23");
+    assert_eq!("23/36".parse::<Rule>().unwrap().to_string(), "B36/S23");
+}
@@ -0,0 +1,298 @@
+use std::io::{Read, Write};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::game::board::Board;
+use crate::game::pattern::Pattern;
+use crate::game::pattern_error::PatternError;
+use crate::game::pattern_io::{parse_rle, write_rle, MAX_PATTERN_CELLS, MAX_PATTERN_SIZE};
+use crate::game::placement::Placement;
+use crate::game::rules::Rule;
+use crate::game::topology::Topology;
+use crate::render::camera::MAX_ZOOM;
+
+// Links come from anyone, so the RLE they inflate to is capped.
+const MAX_RLE_LENGTH: u64 = 16 * 1024 * 1024;
+
+const BASE64URL_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// A starting state small enough to be shared in the fragment of a URL, such as
+/// `pattern=...&x=28&y=23&width=60&height=50&rule=B3/S23&topology=torus&interval=100&cell_size=10`.
+///
+/// The live cells are written as RLE, compressed with deflate and encoded in base64url.
+///
+/// ```
+/// use game_of_life::{parse_pattern, Board, Placement, SharedState};
+///
+/// let mut board = Board::new(20, 10);
+/// let glider = parse_pattern("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+/// board.add_pattern(&glider, &Placement { position: Some((4, 2)), ..Placement::default() }).unwrap();
+///
+/// let fragment = SharedState::from_board(&board, 100, 10).to_fragment();
+/// let shared = SharedState::from_fragment(&fragment).unwrap();
+/// assert_eq!(shared.position, (4, 2));
+/// assert_eq!(shared.to_board().unwrap().cells, board.cells);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct SharedState {
+    /// The live cells, cropped to their bounding box.
+    pub pattern: Pattern,
+    /// Where the top-left corner of the pattern lies on the board.
+    pub position: (usize, usize),
+    /// The width and height of the board, in cells.
+    pub board_size: (usize, usize),
+    /// The rule of the board.
+    pub rule: Rule,
+    /// What lies beyond the edges of the board.
+    pub topology: Topology,
+    /// The time between two generations, in milliseconds.
+    pub update_interval_ms: u64,
+    /// The size of a cell on screen, in pixels.
+    pub cell_size: usize,
+}
+
+impl SharedState {
+    /// Captures the live cells, the rule and the topology of a board, with the speed and cell size it is shown at.
+    pub fn from_board(board: &Board, update_interval_ms: u64, cell_size: usize) -> SharedState {
+        SharedState {
+            pattern: board.to_pattern(),
+            position: board.bounding_box().map_or((0, 0), |(x, y, _, _)| (x, y)),
+            board_size: (board.grid_width, board.grid_height),
+            rule: board.rule,
+            topology: board.topology,
+            update_interval_ms,
+            cell_size,
+        }
+    }
+
+    /// Creates a board of the shared size, rule and topology, with the pattern at its position.
+    pub fn to_board(&self) -> Result<Board, PatternError> {
+        let mut board = Board::new(self.board_size.0, self.board_size.1);
+        board.rule = self.rule;
+        board.topology = self.topology;
+        board.add_pattern(&self.pattern, &self.placement())?;
+        Ok(board)
+    }
+
+    /// Places the pattern at its shared position.
+    pub fn placement(&self) -> Placement {
+        Placement { position: Some(self.position), ..Placement::default() }
+    }
+
+    /// Encodes the state as a URL fragment, without the leading `#`.
+    pub fn to_fragment(&self) -> String {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        // Compressing into memory cannot fail.
+        encoder.write_all(write_rle(&self.pattern).as_bytes()).expect("Cannot compress the pattern");
+        let compressed = encoder.finish().expect("Cannot compress the pattern");
+        format!(
+            "pattern={}&x={}&y={}&width={}&height={}&rule={}&topology={}&interval={}&cell_size={}",
+            encode_base64url(&compressed),
+            self.position.0,
+            self.position.1,
+            self.board_size.0,
+            self.board_size.1,
+            self.rule,
+            self.topology,
+            self.update_interval_ms,
+            self.cell_size
+        )
+    }
+
+    /// Decodes a fragment written by [`SharedState::to_fragment`], with or without its leading `#`.
+    ///
+    /// Every parameter is required, unknown ones are skipped. Links come from anyone, so the board is at most as large
+    /// as a parsed pattern, with the pattern inside it, the cells are at most `MAX_ZOOM` pixels wide and the interval
+    /// is at least a millisecond.
+    pub fn from_fragment(fragment: &str) -> Result<SharedState, String> {
+        let parameters: Vec<(&str, &str)> = fragment
+            .trim_start_matches('#')
+            .split('&')
+            .filter(|parameter| !parameter.is_empty())
+            .map(|parameter| parameter.split_once('=').ok_or_else(|| format!("Invalid parameter '{}' in the link", parameter)))
+            .collect::<Result<_, _>>()?;
+        let value = |key: &str| -> Result<&str, String> {
+            parameters.iter().find(|(name, _)| *name == key).map(|(_, value)| *value)
+                .ok_or_else(|| format!("The link has no '{}' parameter", key))
+        };
+        let parse = |key: &str, range: RangeInclusive<usize>| -> Result<usize, String> {
+            let text = value(key)?;
+            let number = text.parse().map_err(|_| format!("Invalid '{}' parameter '{}' in the link, expected a number", key, text))?;
+            if !range.contains(&number) {
+                return Err(format!("The '{}' parameter of the link must be between {} and {}, got {}", key, range.start(), range.end(), number));
+            }
+            Ok(number)
+        };
+
+        let pattern = decode_pattern(value("pattern")?)?;
+        let board_size = (parse("width", 1..=MAX_PATTERN_SIZE)?, parse("height", 1..=MAX_PATTERN_SIZE)?);
+        if board_size.0.checked_mul(board_size.1).is_none_or(|cells| cells > MAX_PATTERN_CELLS) {
+            return Err(format!("The {}x{} board of the link is larger than {} cells", board_size.0, board_size.1, MAX_PATTERN_CELLS));
+        }
+        if pattern.width > board_size.0 || pattern.height > board_size.1 {
+            return Err(format!("The {}x{} pattern of the link does not fit its {}x{} board", pattern.width, pattern.height, board_size.0, board_size.1));
+        }
+        let position = (
+            parse("x", 0..=board_size.0 - pattern.width)?,
+            parse("y", 0..=board_size.1 - pattern.height)?,
+        );
+        Ok(SharedState {
+            pattern,
+            position,
+            board_size,
+            // The slash of the rule is escaped by some link shorteners.
+            rule: Rule::from_str(&value("rule")?.replace("%2F", "/").replace("%2f", "/"))?,
+            topology: value("topology")?.parse()?,
+            update_interval_ms: parse("interval", 1..=u32::MAX as usize)? as u64,
+            cell_size: parse("cell_size", 1..=MAX_ZOOM as usize)?,
+        })
+    }
+}
+
+fn decode_pattern(encoded: &str) -> Result<Pattern, String> {
+    let compressed = decode_base64url(encoded).ok_or("The pattern of the link is not valid base64url")?;
+    let mut rle = String::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .take(MAX_RLE_LENGTH)
+        .read_to_string(&mut rle)
+        .map_err(|error| format!("Cannot decompress the pattern of the link: {}", error))?;
+    parse_rle(&rle).map_err(|error| error.to_string())
+}
+
+// Base64 with the URL and filename safe alphabet, without padding.
+fn encode_base64url(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            encoded.push(BASE64URL_ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    encoded
+}
+
+// Accepts padded and unpadded input, returns None on any other character.
+fn decode_base64url(encoded: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u32> = encoded.trim_end_matches('=').bytes()
+        .map(|c| BASE64URL_ALPHABET.iter().position(|&digit| digit == c).map(|value| value as u32))
+        .collect::<Option<_>>()?;
+    if digits.len() % 4 == 1 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(digits.len() * 3 / 4);
+    for chunk in digits.chunks(4) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &digit)| bits | digit << (18 - 6 * i));
+        for i in 0..chunk.len() - 1 {
+            bytes.push((bits >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::cell_state::CellState;
+    use crate::game::pattern_io::parse_pattern;
+
+    fn shared_glider() -> SharedState {
+        let mut board = Board::new(30, 20);
+        board.rule = "B36/S23".parse().unwrap();
+        board.topology = Topology::Torus;
+        let glider = parse_pattern("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+        board.add_pattern(&glider, &Placement { position: Some((12, 5)), ..Placement::default() }).unwrap();
+        SharedState::from_board(&board, 80, 6)
+    }
+
+    #[test]
+    fn states_go_through_fragments_unchanged() {
+        let shared = shared_glider();
+        let fragment = shared.to_fragment();
+        assert!(fragment.ends_with("&x=12&y=5&width=30&height=20&rule=B36/S23&topology=torus&interval=80&cell_size=6"));
+        assert_eq!(SharedState::from_fragment(&format!("#{}", fragment)), Ok(shared.clone()));
+
+        let board = shared.to_board().unwrap();
+        assert_eq!(board.population(), 5);
+        assert_eq!(board.get_cell(13, 5), CellState::Alive);
+        assert_eq!(board.topology, Topology::Torus);
+    }
+
+    #[test]
+    fn fragments_only_use_url_safe_characters() {
+        let fragment = shared_glider().to_fragment();
+        assert!(fragment.chars().all(|c| c.is_ascii_alphanumeric() || "-_=&/".contains(c)), "{}", fragment);
+    }
+
+    #[test]
+    fn empty_boards_can_be_shared() {
+        let shared = SharedState::from_board(&Board::new(8, 8), 100, 10);
+        assert_eq!(SharedState::from_fragment(&shared.to_fragment()).unwrap().to_board().unwrap().population(), 0);
+    }
+
+    #[test]
+    fn base64url_round_trips_every_length() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        for length in 0..10 {
+            assert_eq!(decode_base64url(&encode_base64url(&bytes[250 - length..250])), Some(bytes[250 - length..250].to_vec()));
+        }
+        assert_eq!(encode_base64url(&[0xfb, 0xff]), "-_8");
+        assert_eq!(decode_base64url("-_8="), Some(vec![0xfb, 0xff]));
+        assert_eq!(decode_base64url("a+b/"), None);
+        assert_eq!(decode_base64url("abcde"), None);
+    }
+
+    #[test]
+    fn invalid_links_are_rejected() {
+        let fragment = shared_glider().to_fragment();
+        let without = |key: &str| fragment.split('&').filter(|parameter| !parameter.starts_with(key)).collect::<Vec<_>>().join("&");
+
+        assert_eq!(SharedState::from_fragment(&without("x=")), Err("The link has no 'x' parameter".to_string()));
+        assert!(SharedState::from_fragment(&without("pattern=")).is_err());
+        assert!(SharedState::from_fragment(&fragment.replace("width=30", "width=wide")).is_err());
+        assert!(SharedState::from_fragment(&fragment.replace("topology=torus", "topology=sphere")).is_err());
+        assert!(SharedState::from_fragment("pattern=AAAA&x=0").is_err());
+        assert!(SharedState::from_fragment("pattern").is_err());
+        assert!(SharedState::from_fragment(&format!("{}&theme=dark", fragment)).is_ok());
+    }
+
+    #[test]
+    fn out_of_range_parameters_are_rejected() {
+        let fragment = shared_glider().to_fragment();
+        for (parameter, replacement) in [
+            ("x=12", "x=28"),
+            ("y=5", "y=3000000000"),
+            ("width=30", "width=2"),
+            ("width=30", "width=0"),
+            ("width=30", "width=18446744073709551616"),
+            ("height=20", "height=200000"),
+            ("interval=80", "interval=0"),
+            ("cell_size=6", "cell_size=0"),
+            ("cell_size=6", "cell_size=4611686018427387904"),
+        ] {
+            let result = SharedState::from_fragment(&fragment.replace(parameter, replacement));
+            assert!(result.is_err(), "{} was accepted", replacement);
+        }
+        assert_eq!(SharedState::from_fragment(&fragment.replace("cell_size=6", "cell_size=65")),
+                   Err("The 'cell_size' parameter of the link must be between 1 and 64, got 65".to_string()));
+        assert!(SharedState::from_fragment(&fragment.replace("width=30&height=20", "width=100000&height=100000")).is_err());
+        // 65536 x 65536 cells wrap to 0 with a 32-bit usize, as on wasm32.
+        assert_eq!(SharedState::from_fragment(&fragment.replace("width=30&height=20", "width=65536&height=65536")),
+                   Err(format!("The 65536x65536 board of the link is larger than {} cells", MAX_PATTERN_CELLS)));
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(b"x = 3000000000, y = 3000000000\no!").unwrap();
+        let huge = encode_base64url(&encoder.finish().unwrap());
+        let (_, parameters) = fragment.split_once('&').unwrap();
+        assert!(SharedState::from_fragment(&format!("pattern={}&{}", huge, parameters)).is_err());
+    }
+
+    #[test]
+    fn patterns_outside_the_board_cannot_be_placed() {
+        let shared = SharedState { position: (29, 5), ..shared_glider() };
+        assert!(shared.to_board().is_err());
+    }
+}
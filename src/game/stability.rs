@@ -0,0 +1,135 @@
+use crate::game::board::Board;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// How a board behaves once it has settled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stability {
+    /// All the cells are dead.
+    Extinct,
+    /// The board no longer changes.
+    StillLife,
+    /// The board comes back to the same state every `period` generations.
+    Oscillator {
+        /// Number of generations between two identical states.
+        period: usize,
+    },
+}
+
+impl fmt::Display for Stability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stability::Extinct => write!(f, "extinct"),
+            Stability::StillLife => write!(f, "still life"),
+            Stability::Oscillator { period } => write!(f, "period {} oscillator", period),
+        }
+    }
+}
+
+/// Detects when a board comes back to one of its recent states.
+///
+/// The detector remembers a hash of the last `max_period` boards it observed, so it finds
+/// oscillators with a period up to `max_period`.
+#[derive(Clone, Debug)]
+pub struct StabilityDetector {
+    max_period: usize,
+    history: VecDeque<u64>,
+}
+
+impl StabilityDetector {
+    /// Creates a detector for periods up to `max_period` generations.
+    pub fn new(max_period: usize) -> StabilityDetector {
+        StabilityDetector { max_period, history: VecDeque::with_capacity(max_period) }
+    }
+
+    /// Records the board of the next generation and returns how it behaves, once that is known.
+    pub fn observe(&mut self, board: &Board) -> Option<Stability> {
+        if board.population() == 0 {
+            self.history.clear();
+            return Some(Stability::Extinct);
+        }
+
+        let mut hasher = DefaultHasher::new();
+        board.cells.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let period = self.history.iter().rev().position(|previous| *previous == hash).map(|index| index + 1);
+        if self.history.len() == self.max_period {
+            self.history.pop_front();
+        }
+        if self.max_period > 0 {
+            self.history.push_back(hash);
+        }
+
+        match period {
+            Some(1) => Some(Stability::StillLife),
+            Some(period) => Some(Stability::Oscillator { period }),
+            None => None,
+        }
+    }
+
+    /// Forgets the observed boards, for instance after the board was edited.
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::cell_state::CellState;
+
+    fn observe_generations(board: &mut Board, detector: &mut StabilityDetector, generations: usize) -> Option<Stability> {
+        let mut stability = detector.observe(board);
+        for _ in 0..generations {
+            board.update();
+            stability = detector.observe(board);
+        }
+        stability
+    }
+
+    #[test]
+    fn test_extinct() {
+        let mut board = Board::new(5, 5);
+        board.set_cell(2, 2, CellState::Alive);
+        let mut detector = StabilityDetector::new(10);
+        assert_eq!(observe_generations(&mut board, &mut detector, 1), Some(Stability::Extinct));
+    }
+
+    #[test]
+    fn test_still_life() {
+        let mut board = Board::new(4, 4);
+        for (x, y) in [(1, 1), (1, 2), (2, 1), (2, 2)] {
+            board.set_cell(x, y, CellState::Alive);
+        }
+        let mut detector = StabilityDetector::new(10);
+        assert_eq!(detector.observe(&board), None);
+        assert_eq!(observe_generations(&mut board, &mut detector, 1), Some(Stability::StillLife));
+    }
+
+    #[test]
+    fn test_blinker_oscillates() {
+        let mut board = Board::new(5, 5);
+        for x in 1..4 {
+            board.set_cell(x, 2, CellState::Alive);
+        }
+        let mut detector = StabilityDetector::new(10);
+        assert_eq!(observe_generations(&mut board, &mut detector, 1), None);
+        board.update();
+        assert_eq!(detector.observe(&board), Some(Stability::Oscillator { period: 2 }));
+        assert_eq!(Stability::Oscillator { period: 2 }.to_string(), "period 2 oscillator");
+    }
+
+    #[test]
+    fn test_period_longer_than_history() {
+        let mut board = Board::new(5, 5);
+        for x in 1..4 {
+            board.set_cell(x, 2, CellState::Alive);
+        }
+        let mut detector = StabilityDetector::new(1);
+        assert_eq!(observe_generations(&mut board, &mut detector, 6), None);
+    }
+}
@@ -0,0 +1,33 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// What lies beyond the edges of a board.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Topology {
+    /// Cells outside the board are always dead.
+    #[default]
+    Bounded,
+    /// Opposite edges are joined, so patterns leaving one side come back on the other.
+    Torus,
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bounded" => Ok(Topology::Bounded),
+            "torus" => Ok(Topology::Torus),
+            _ => Err(format!("Unknown topology '{}', expected one of: bounded, torus", s)),
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Topology::Bounded => write!(f, "bounded"),
+            Topology::Torus => write!(f, "torus"),
+        }
+    }
+}
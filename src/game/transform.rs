@@ -0,0 +1,119 @@
+use std::str::FromStr;
+
+/// One of the 8 rotations and reflections of a rectangle (the D4 group).
+#[derive(Clone, PartialEq, Debug, Copy)]
+pub enum Transform {
+    /// Leaves the pattern unchanged.
+    Identity,
+    /// Rotates a quarter turn clockwise.
+    Rotate90,
+    /// Rotates a half turn.
+    Rotate180,
+    /// Rotates a quarter turn counter-clockwise.
+    Rotate270,
+    /// Mirrors left and right.
+    FlipHorizontal,
+    /// Mirrors top and bottom.
+    FlipVertical,
+    /// Mirrors along the main diagonal, swapping x and y.
+    FlipDiagonal,
+    /// Mirrors along the anti-diagonal.
+    FlipAntiDiagonal,
+}
+
+impl Transform {
+    /// All the transforms.
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::FlipHorizontal,
+        Transform::FlipVertical,
+        Transform::FlipDiagonal,
+        Transform::FlipAntiDiagonal,
+    ];
+
+    /// Returns the size of a `width` x `height` rectangle once transformed.
+    pub fn transformed_size(&self, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            Transform::Identity | Transform::Rotate180 | Transform::FlipHorizontal | Transform::FlipVertical => (width, height),
+            _ => (height, width),
+        }
+    }
+
+    /// Maps (`x`, `y`) of a `width` x `height` rectangle to its coordinates once transformed.
+    /// Rotations are clockwise, with y growing downwards.
+    pub fn apply(&self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            Transform::Identity => (x, y),
+            Transform::Rotate90 => (height - 1 - y, x),
+            Transform::Rotate180 => (width - 1 - x, height - 1 - y),
+            Transform::Rotate270 => (y, width - 1 - x),
+            Transform::FlipHorizontal => (width - 1 - x, y),
+            Transform::FlipVertical => (x, height - 1 - y),
+            Transform::FlipDiagonal => (y, x),
+            Transform::FlipAntiDiagonal => (height - 1 - y, width - 1 - x),
+        }
+    }
+}
+
+impl FromStr for Transform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "identity" | "none" => Ok(Transform::Identity),
+            "rot90" => Ok(Transform::Rotate90),
+            "rot180" => Ok(Transform::Rotate180),
+            "rot270" => Ok(Transform::Rotate270),
+            "flip-h" => Ok(Transform::FlipHorizontal),
+            "flip-v" => Ok(Transform::FlipVertical),
+            "flip-diag" => Ok(Transform::FlipDiagonal),
+            "flip-antidiag" => Ok(Transform::FlipAntiDiagonal),
+            _ => Err(format!(
+                "Unknown transform '{}', expected one of: identity, rot90, rot180, rot270, flip-h, flip-v, flip-diag, flip-antidiag",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate90_swaps_size() {
+        assert_eq!(Transform::Rotate90.transformed_size(3, 2), (2, 3));
+        assert_eq!(Transform::Rotate180.transformed_size(3, 2), (3, 2));
+    }
+
+    #[test]
+    fn test_rotate90_corners() {
+        // Top-left goes to top-right, bottom-left goes to top-left.
+        assert_eq!(Transform::Rotate90.apply(0, 0, 3, 2), (1, 0));
+        assert_eq!(Transform::Rotate90.apply(0, 1, 3, 2), (0, 0));
+        assert_eq!(Transform::Rotate90.apply(2, 1, 3, 2), (0, 2));
+    }
+
+    #[test]
+    fn test_transforms_stay_in_bounds() {
+        for transform in Transform::ALL {
+            let (new_width, new_height) = transform.transformed_size(4, 3);
+            for x in 0..4 {
+                for y in 0..3 {
+                    let (new_x, new_y) = transform.apply(x, y, 4, 3);
+                    assert!(new_x < new_width && new_y < new_height, "{:?} maps ({}, {}) out of bounds", transform, x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_transform() {
+        assert_eq!("rot270".parse::<Transform>(), Ok(Transform::Rotate270));
+        assert_eq!("FLIP-H".parse::<Transform>(), Ok(Transform::FlipHorizontal));
+        assert!("rot45".parse::<Transform>().is_err());
+    }
+}
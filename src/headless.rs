@@ -0,0 +1,87 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+
+use game_of_life::{export_animation, write_pattern, write_snapshot, AnimationOptions, Board, Census, GameState, PatternFormat,
+                   SnapshotOptions, StabilityDetector};
+
+use crate::cli::Options;
+
+const DEFAULT_MAX_GENERATIONS: u64 = 100_000;
+const MAX_PERIOD: usize = 100;
+const DEFAULT_ANIMATION_DELAY_MS: u32 = 100;
+
+pub fn run(mut game_state: GameState, options: &Options) -> Result<(), String> {
+    let max_generations = options.generations.unwrap_or(DEFAULT_MAX_GENERATIONS);
+
+    let drawing = options.animation_filename.is_some() || options.snapshot_filename.is_some();
+    if drawing && options.color_mode.needs_ages() {
+        game_state.board.set_age_tracking(true);
+    }
+    // The animation runs the game again from the start, drawing one generation at a time.
+    let start = options.animation_filename.is_some().then(|| game_state.clone());
+
+    let mut detector = StabilityDetector::new(MAX_PERIOD);
+    let mut stability = detector.observe(&game_state.board);
+    for _ in 0..max_generations {
+        if options.until_stable && stability.is_some() {
+            break;
+        }
+        game_state.update();
+        stability = detector.observe(&game_state.board);
+    }
+
+    if let (Some(filename), Some(mut start)) = (&options.animation_filename, start) {
+        let generations = game_state.generation - start.generation;
+        write_run_animation(&mut start, generations, filename, options)?;
+    }
+    if let Some(filename) = &options.snapshot_filename {
+        write_board_snapshot(&game_state.board, filename, options)?;
+    }
+
+    let text = write_pattern(&game_state.board.to_pattern(), options.output_format.unwrap_or(PatternFormat::Rle));
+    match &options.output_filename {
+        Some(filename) => {
+            fs::write(filename, text).map_err(|error| format!("Cannot write '{}': {}", filename, error))?;
+        }
+        None => print!("{}", text),
+    }
+
+    let state = stability.map_or("not stabilised".to_string(), |stability| stability.to_string());
+    eprintln!("Generation {}, population {}, {}", game_state.generation, game_state.board.population(), state);
+    if options.census {
+        eprintln!("Census: {}", Census::of_board(&game_state.board));
+    }
+    Ok(())
+}
+
+fn write_run_animation(start: &mut GameState, generations: u64, filename: &str, options: &Options) -> Result<(), String> {
+    let animation_options = AnimationOptions {
+        cell_size: options.cell_size,
+        palette: options.theme.palette(),
+        color_mode: options.color_mode,
+        delay_ms: options.animation_delay_ms.unwrap_or(DEFAULT_ANIMATION_DELAY_MS),
+        crop: options.crop,
+    };
+    write_file(filename, |writer| export_animation(start, generations, options.animation_format, &animation_options, writer))
+}
+
+fn write_board_snapshot(board: &Board, filename: &str, options: &Options) -> Result<(), String> {
+    let snapshot_options = SnapshotOptions {
+        cell_size: options.cell_size,
+        palette: options.theme.palette(),
+        color_mode: options.color_mode,
+        grid_lines: options.grid_lines,
+        crop: options.crop,
+    };
+    write_file(filename, |writer| write_snapshot(board, options.snapshot_format, &snapshot_options, writer))
+}
+
+// Creates the file and writes it through a buffer, flushed at the end so that write errors are not lost.
+fn write_file(filename: &str, write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>) -> Result<(), String> {
+    let result = File::create(filename).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        writer.flush()
+    });
+    result.map_err(|error| format!("Cannot write '{}': {}", filename, error))
+}
@@ -0,0 +1,43 @@
+//! Conway's Game of Life engine, used by the desktop and web front ends of this repository.
+//!
+//! ```
+//! use game_of_life::{parse_pattern, Board, CellState, Placement};
+//!
+//! let mut board = Board::new(5, 5);
+//! let blinker = parse_pattern("XXX").unwrap();
+//! board.add_pattern(&blinker, &Placement::default()).unwrap();
+//! board.update();
+//! assert_eq!(board.cells[2][1], CellState::Alive);
+//! assert_eq!(board.cells[1][2], CellState::Dead);
+//! ```
+#![warn(missing_docs)]
+
+/// Writing boards and runs as image files.
+pub mod export;
+/// The game engine.
+pub mod game;
+/// Helpers shared by the front ends to draw the board.
+pub mod render;
+
+pub use export::animation::{export_animation, record_generations, render_frames, write_animation, AnimationFormat, AnimationOptions};
+pub use export::snapshot::{render_snapshot, write_png, write_snapshot, write_svg, SnapshotFormat, SnapshotOptions};
+pub use game::board::Board;
+pub use game::cell_state::CellState;
+pub use game::census::{apgcode, object_name, separate_objects, Census, CensusObject, MAX_OBJECT_PERIOD};
+pub use game::game_state::GameState;
+pub use game::image_import::{is_image_path, parse_image_pattern, read_image_pattern, ImageImportOptions, MAX_IMAGE_PIXELS};
+pub use game::paste_mode::PasteMode;
+pub use game::pattern::Pattern;
+pub use game::pattern_error::PatternError;
+pub use game::pattern_io::{parse_pattern, parse_plaintext, parse_rle, read_pattern_file, write_pattern, write_rle, PatternFormat, MAX_PATTERN_CELLS,
+                           MAX_PATTERN_SIZE};
+pub use game::placement::Placement;
+pub use game::rules::{evolve_cell, Rule};
+pub use game::share::SharedState;
+pub use game::stability::{Stability, StabilityDetector};
+pub use game::topology::Topology;
+pub use game::transform::Transform;
+pub use render::camera::{Camera, CameraMode};
+pub use render::font::text_width;
+pub use render::frame::{Frame, Rgba};
+pub use render::theme::{ColorMode, Palette, Theme};
@@ -1,61 +1,124 @@
-mod game;
+mod cli;
+mod headless;
+mod soup_search;
 mod view;
 
-use std::{env, io};
-use std::fs::File;
-use std::io::BufRead;
+#[cfg(not(any(all(any(feature = "desktop", feature = "terminal"), not(target_arch = "wasm32")), all(feature = "web", target_arch = "wasm32"))))]
+compile_error!("The game_of_life binary needs a front end: enable the `desktop` or `terminal` feature for native targets or the `web` feature for wasm32.");
 
+use std::{env, process};
+
+use game_of_life::{is_image_path, read_image_pattern, read_pattern_file, GameState, Pattern, PatternError};
+
+use crate::cli::{Command, Options, ViewKind};
+#[cfg(any(all(feature = "desktop", not(target_arch = "wasm32")), all(feature = "web", target_arch = "wasm32")))]
 use crate::view::game_view::GameView;
-use crate::game::game_state::GameState;
 use crate::view::game_view_trait::GameViewTrait;
+#[cfg(all(feature = "terminal", not(target_arch = "wasm32")))]
+use crate::view::terminal::game_view::GameView as TerminalGameView;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let pattern = if args.len() > 1 {
-        let pattern_filename = &args[1];
-        Some(read_file(pattern_filename).unwrap())
-    } else {
-        None
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match cli::parse_args(&args) {
+        Ok(Command::Run(options)) => options,
+        Ok(Command::Help) => {
+            print!("{}", cli::USAGE);
+            return;
+        }
+        Err(message) => exit_with_error(&format!("{}\nRun with --help for usage.", message), 2),
     };
-    let cell_size: u8 = 3;
 
-    initialize_game(pattern, cell_size);
+    // A link shared from the web page starts the game it encodes.
+    #[cfg(all(feature = "web", target_arch = "wasm32"))]
+    if let Some((pattern, options)) = view::web::share::shared_start(&options) {
+        if let Err(message) = initialize_game(Some(pattern), &options) {
+            exit_with_error(&message, 1);
+        }
+        return;
+    }
+
+    if let Some(filename) = &options.soup_search_filename {
+        if let Err(message) = soup_search::run(filename, &options) {
+            exit_with_error(&message, 1);
+        }
+        return;
+    }
+
+    let result = options.pattern_filename.as_deref()
+        .map(|path| read_pattern(path, &options))
+        .transpose()
+        .map_err(|error| error.to_string())
+        .and_then(|pattern| {
+            if options.headless {
+                let game_state = new_game_state(pattern, &options).map_err(|error| error.to_string())?;
+                headless::run(game_state, &options)
+            } else {
+                initialize_game(pattern, &options)
+            }
+        });
+    if let Err(message) = result {
+        exit_with_error(&message, 1);
+    }
+}
+
+fn read_pattern(path: &str, options: &Options) -> Result<Pattern, PatternError> {
+    if is_image_path(path) {
+        read_image_pattern(path, &options.image_import)
+    } else {
+        read_pattern_file(path)
+    }
 }
 
-fn initialize_game(pattern: Option<Vec<String>>, cell_size: u8) {
-    let initial_alive_probability = 0.1;
+fn exit_with_error(message: &str, exit_code: i32) -> ! {
+    eprintln!("Error: {}", message);
+    process::exit(exit_code);
+}
 
-    let update_interval_ms: usize = 300;
-    let board_width: usize = 600;
-    let board_height: usize = 500;
+fn initialize_game(pattern: Option<Pattern>, options: &Options) -> Result<(), String> {
+    let clipboard = pattern.clone();
+    let game_state = new_game_state(pattern, options).map_err(|error| error.to_string())?;
 
-    let mut game_state = GameState::new(board_width, board_height, cell_size as usize);
+    let mut view = new_view(game_state, options)?;
+    let controller = view.controller();
+    controller.clipboard = clipboard;
+    controller.theme = options.theme;
+    controller.set_color_mode(options.color_mode);
+    controller.keep_board_size = options.keep_board_size;
+    view.init()
+}
 
-    initialize_game_state(&mut game_state, pattern, initial_alive_probability);
+fn new_view(game_state: GameState, options: &Options) -> Result<Box<dyn GameViewTrait>, String> {
+    match options.view {
+        #[cfg(any(all(feature = "desktop", not(target_arch = "wasm32")), all(feature = "web", target_arch = "wasm32")))]
+        ViewKind::Desktop => Ok(Box::new(GameView::new(game_state, options.cell_size, options.update_interval_ms))),
+        #[cfg(all(feature = "terminal", not(target_arch = "wasm32")))]
+        ViewKind::Terminal => Ok(Box::new(TerminalGameView::new(game_state, options.cell_size, options.update_interval_ms))),
+        #[allow(unreachable_patterns)]
+        view => Err(format!("The {} view is not available in this build", view)),
+    }
+}
 
-    let mut view = Box::new(GameView::new(game_state, cell_size as usize, update_interval_ms));
-    view.init().expect("Error initializing the main game loop.");
+fn new_game_state(pattern: Option<Pattern>, options: &Options) -> Result<GameState, PatternError> {
+    let mut game_state = GameState::new(options.width, options.height, options.cell_size);
+    initialize_game_state(&mut game_state, pattern, options)?;
+    Ok(game_state)
 }
 
-fn initialize_game_state(game_state: &mut GameState, pattern: Option<Vec<String>>, initial_alive_probability: f64) {
+fn initialize_game_state(game_state: &mut GameState, pattern: Option<Pattern>, options: &Options) -> Result<(), PatternError> {
+    let rule = options.rule.or(pattern.as_ref().and_then(|p| p.rule)).unwrap_or_default();
+    game_state.set_rule(rule);
+    game_state.set_topology(options.topology);
+
     match pattern {
         Some(p) => {
-            game_state.add_pattern(p);
-        }
-        None => {
-            game_state.init(initial_alive_probability);
+            game_state.add_pattern(&p, &options.placement)?;
         }
+        None => match options.seed {
+            Some(seed) => game_state.init_with_seed(options.initial_alive_probability, seed),
+            None => game_state.init(options.initial_alive_probability),
+        },
     }
-}
 
-fn read_file(filename: &str) -> io::Result<Vec<String>> {
-    match File::open(filename) {
-        Ok(file) => {
-            let reader = io::BufReader::new(file);
-            Ok(reader.lines().filter_map(Result::ok).collect())
-        }
-        Err(e) => {
-            Err(e)
-        }
-    }
-}
\ No newline at end of file
+    game_state.advance(options.start_generation);
+    Ok(())
+}
@@ -0,0 +1,216 @@
+use crate::game::board::Board;
+
+/// Smallest zoom, where a pixel shows 16x16 cells.
+pub const MIN_ZOOM: f64 = 1.0 / 16.0;
+/// Largest zoom, where a cell is 64 pixels wide.
+pub const MAX_ZOOM: f64 = 64.0;
+
+const ZOOM_STEP: f64 = 2.0;
+// Share of the viewport left around the pattern when fitting it.
+const FIT_MARGIN: f64 = 0.1;
+
+/// How the camera moves by itself between frames.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CameraMode {
+    /// The camera only moves when asked to.
+    #[default]
+    Free,
+    /// The camera keeps all the live cells in view.
+    FitPattern,
+    /// The camera stays centred on the centroid of the live cells.
+    FollowCentroid,
+}
+
+/// Maps board cells to screen pixels, independently of the size of the board.
+///
+/// Board coordinates are in cells, with (0, 0) the top-left corner of the first cell; screen
+/// coordinates are in pixels, with (0, 0) the top-left corner of the viewport.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Camera {
+    /// The board point shown at the centre of the viewport.
+    pub center: (f64, f64),
+    /// The width of a cell in pixels, below 1 when several cells share a pixel.
+    pub zoom: f64,
+    /// The size of the viewport in pixels.
+    pub viewport: (f64, f64),
+    /// How the camera moves by itself, see [`Camera::follow`].
+    pub mode: CameraMode,
+}
+
+impl Camera {
+    /// Creates a camera showing the top-left corner of the board with cells `zoom` pixels wide.
+    pub fn new(viewport_width: f64, viewport_height: f64, zoom: f64) -> Camera {
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        Camera {
+            center: (viewport_width / 2.0 / zoom, viewport_height / 2.0 / zoom),
+            zoom,
+            viewport: (viewport_width, viewport_height),
+            mode: CameraMode::Free,
+        }
+    }
+
+    /// Returns the board point under a screen point.
+    pub fn to_board(&self, screen_x: f64, screen_y: f64) -> (f64, f64) {
+        (
+            self.center.0 + (screen_x - self.viewport.0 / 2.0) / self.zoom,
+            self.center.1 + (screen_y - self.viewport.1 / 2.0) / self.zoom,
+        )
+    }
+
+    /// Returns the screen point showing a board point.
+    pub fn to_screen(&self, board_x: f64, board_y: f64) -> (f64, f64) {
+        (
+            (board_x - self.center.0) * self.zoom + self.viewport.0 / 2.0,
+            (board_y - self.center.1) * self.zoom + self.viewport.1 / 2.0,
+        )
+    }
+
+    /// Returns the cell under a screen point, if it is on the board.
+    pub fn cell_at(&self, board: &Board, screen_x: f64, screen_y: f64) -> Option<(usize, usize)> {
+        let (x, y) = self.to_board(screen_x, screen_y);
+        if x < 0.0 || y < 0.0 || x >= board.grid_width as f64 || y >= board.grid_height as f64 {
+            return None;
+        }
+        Some((x as usize, y as usize))
+    }
+
+    /// Returns the cells at least partly visible, as `(x, y, width, height)` clipped to the board.
+    pub fn visible_cells(&self, board: &Board) -> (usize, usize, usize, usize) {
+        let (left, top) = self.to_board(0.0, 0.0);
+        let (right, bottom) = self.to_board(self.viewport.0, self.viewport.1);
+        let clip = |value: f64, size: usize| value.clamp(0.0, size as f64) as usize;
+        let (x, y) = (clip(left.floor(), board.grid_width), clip(top.floor(), board.grid_height));
+        let (end_x, end_y) = (clip(right.ceil(), board.grid_width), clip(bottom.ceil(), board.grid_height));
+        (x, y, end_x - x, end_y - y)
+    }
+
+    /// Multiplies the zoom by `factor`, keeping the board point under the given screen point in place.
+    pub fn zoom_at(&mut self, factor: f64, screen_x: f64, screen_y: f64) {
+        let before = self.to_board(screen_x, screen_y);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let after = self.to_board(screen_x, screen_y);
+        self.center = (self.center.0 + before.0 - after.0, self.center.1 + before.1 - after.1);
+    }
+
+    /// Doubles the zoom around the centre of the viewport.
+    pub fn zoom_in(&mut self) {
+        self.zoom_at(ZOOM_STEP, self.viewport.0 / 2.0, self.viewport.1 / 2.0);
+    }
+
+    /// Halves the zoom around the centre of the viewport.
+    pub fn zoom_out(&mut self) {
+        self.zoom_at(1.0 / ZOOM_STEP, self.viewport.0 / 2.0, self.viewport.1 / 2.0);
+    }
+
+    /// Moves the view by a distance in pixels, dragging the board along, and switches to [`CameraMode::Free`].
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        self.center = (self.center.0 - dx / self.zoom, self.center.1 - dy / self.zoom);
+        self.mode = CameraMode::Free;
+    }
+
+    /// Centres the view on the given board region and zooms so that it fills most of the viewport.
+    pub fn fit(&mut self, x: usize, y: usize, width: usize, height: usize) {
+        let (width, height) = (width.max(1) as f64, height.max(1) as f64);
+        self.center = (x as f64 + width / 2.0, y as f64 + height / 2.0);
+        let zoom = (self.viewport.0 / width).min(self.viewport.1 / height) * (1.0 - FIT_MARGIN);
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    /// Changes the size of the viewport, keeping the same board point at its centre.
+    pub fn resize(&mut self, viewport_width: f64, viewport_height: f64) {
+        self.viewport = (viewport_width, viewport_height);
+    }
+
+    /// Moves the camera as required by its [`CameraMode`], to be called before drawing each frame.
+    /// Nothing moves while the board is empty.
+    pub fn follow(&mut self, board: &Board) {
+        match self.mode {
+            CameraMode::Free => {}
+            CameraMode::FitPattern => {
+                if let Some((x, y, width, height)) = board.bounding_box() {
+                    self.fit(x, y, width, height);
+                }
+            }
+            CameraMode::FollowCentroid => {
+                if let Some(centroid) = board.centroid() {
+                    self.center = centroid;
+                }
+            }
+        }
+    }
+
+    /// Switches to `mode`, or back to [`CameraMode::Free`] if it is the current mode.
+    pub fn toggle_mode(&mut self, mode: CameraMode) {
+        self.mode = if self.mode == mode { CameraMode::Free } else { mode };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::cell_state::CellState;
+
+    #[test]
+    fn test_new_camera_shows_top_left_corner() {
+        let camera = Camera::new(600.0, 500.0, 3.0);
+        assert_eq!(camera.to_board(0.0, 0.0), (0.0, 0.0));
+        assert_eq!(camera.to_screen(200.0, 0.0), (600.0, 0.0));
+    }
+
+    #[test]
+    fn test_screen_and_board_round_trip() {
+        let mut camera = Camera::new(100.0, 80.0, 4.0);
+        camera.pan(13.0, -7.0);
+        camera.zoom_at(3.0, 10.0, 20.0);
+        let (x, y) = camera.to_board(42.0, 17.0);
+        let (screen_x, screen_y) = camera.to_screen(x, y);
+        assert!((screen_x - 42.0).abs() < 1e-9 && (screen_y - 17.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_zoom_keeps_point_under_cursor() {
+        let mut camera = Camera::new(100.0, 100.0, 1.0);
+        let before = camera.to_board(30.0, 60.0);
+        camera.zoom_at(4.0, 30.0, 60.0);
+        assert_eq!(camera.to_board(30.0, 60.0), before);
+        assert_eq!(camera.zoom, 4.0);
+
+        camera.zoom_at(1000.0, 0.0, 0.0);
+        assert_eq!(camera.zoom, MAX_ZOOM);
+        camera.zoom_at(1e-9, 0.0, 0.0);
+        assert_eq!(camera.zoom, MIN_ZOOM);
+    }
+
+    #[test]
+    fn test_visible_cells_and_cell_at() {
+        let board = Board::new(100, 100);
+        let mut camera = Camera::new(40.0, 20.0, 2.0);
+        assert_eq!(camera.visible_cells(&board), (0, 0, 20, 10));
+        assert_eq!(camera.cell_at(&board, 5.0, 5.0), Some((2, 2)));
+
+        camera.pan(10.0, 10.0);
+        assert_eq!(camera.visible_cells(&board), (0, 0, 15, 5));
+        assert_eq!(camera.cell_at(&board, 5.0, 5.0), None);
+    }
+
+    #[test]
+    fn test_fit_and_follow_modes() {
+        let mut board = Board::new(100, 100);
+        board.set_cell(10, 20, CellState::Alive);
+        board.set_cell(29, 29, CellState::Alive);
+
+        let mut camera = Camera::new(200.0, 100.0, 1.0);
+        camera.toggle_mode(CameraMode::FitPattern);
+        camera.follow(&board);
+        assert_eq!(camera.center, (20.0, 25.0));
+        assert_eq!(camera.zoom, 9.0);
+
+        camera.toggle_mode(CameraMode::FollowCentroid);
+        camera.follow(&board);
+        assert_eq!(camera.center, (20.0, 25.0));
+
+        camera.pan(1.0, 0.0);
+        assert_eq!(camera.mode, CameraMode::Free);
+    }
+}
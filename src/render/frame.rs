@@ -0,0 +1,168 @@
+use crate::game::board::Board;
+use crate::render::camera::Camera;
+use crate::render::theme::{ColorMode, Palette};
+
+/// A colour as red, green, blue and alpha bytes.
+pub type Rgba = [u8; 4];
+
+/// An RGBA pixel buffer the board is drawn into, ready to be uploaded as a texture or image.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Frame {
+    /// Width in pixels.
+    pub width: usize,
+    /// Height in pixels.
+    pub height: usize,
+    /// Four bytes per pixel, row by row from the top-left corner.
+    pub pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// Creates a transparent frame.
+    pub fn new(width: usize, height: usize) -> Frame {
+        Frame { width, height, pixels: vec![0; width * height * 4] }
+    }
+
+    /// Changes the size of the frame, which becomes transparent if the size changed.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        if (width, height) != (self.width, self.height) {
+            *self = Frame::new(width, height);
+        }
+    }
+
+    /// Returns the colour of a pixel.
+    pub fn pixel(&self, x: usize, y: usize) -> Rgba {
+        let offset = (y * self.width + x) * 4;
+        [self.pixels[offset], self.pixels[offset + 1], self.pixels[offset + 2], self.pixels[offset + 3]]
+    }
+
+    /// Sets the colour of a pixel, ignoring coordinates outside the frame.
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Rgba) {
+        if x < self.width && y < self.height {
+            let offset = (y * self.width + x) * 4;
+            self.pixels[offset..offset + 4].copy_from_slice(&color);
+        }
+    }
+
+    /// Paints a rectangle, clipped to the frame. A translucent colour is blended with the pixels below.
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Rgba) {
+        let alpha = color[3] as u32;
+        for row in y.min(self.height)..y.saturating_add(height).min(self.height) {
+            for column in x.min(self.width)..x.saturating_add(width).min(self.width) {
+                let offset = (row * self.width + column) * 4;
+                let pixel = &mut self.pixels[offset..offset + 4];
+                for (channel, source) in pixel[..3].iter_mut().zip(color) {
+                    *channel = ((source as u32 * alpha + *channel as u32 * (255 - alpha) + 127) / 255) as u8;
+                }
+                pixel[3] = (alpha + (pixel[3] as u32 * (255 - alpha) + 127) / 255) as u8;
+            }
+        }
+    }
+
+    /// Draws the part of the board seen by `camera`, which should have the size of the frame as its viewport.
+    ///
+    /// When several cells share a pixel, the pixel gets the average colour of these cells, so that large boards
+    /// keep their structure when zoomed out.
+    pub fn draw_board(&mut self, board: &Board, camera: &Camera, palette: &Palette, mode: ColorMode) {
+        let columns = pixel_spans(self.width, board.grid_width, |x| camera.to_board(x, 0.0).0);
+        let rows = pixel_spans(self.height, board.grid_height, |y| camera.to_board(0.0, y).1);
+
+        for (y, rows) in rows.iter().enumerate() {
+            for (x, columns) in columns.iter().enumerate() {
+                let color = match (columns, rows) {
+                    (Some(columns), Some(rows)) if columns.len() == 1 && rows.len() == 1 => {
+                        palette.cell_color(board, columns.start, rows.start, mode)
+                    }
+                    (Some(columns), Some(rows)) => {
+                        let mut sum = [0u32; 4];
+                        for x in columns.clone() {
+                            for y in rows.clone() {
+                                let color = palette.cell_color(board, x, y, mode);
+                                for (total, channel) in sum.iter_mut().zip(color) {
+                                    *total += channel as u32;
+                                }
+                            }
+                        }
+                        let count = (columns.len() * rows.len()) as u32;
+                        sum.map(|total| ((total + count / 2) / count) as u8)
+                    }
+                    _ => palette.outside,
+                };
+                let offset = (y * self.width + x) * 4;
+                self.pixels[offset..offset + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+// Returns, for each of the `pixels` pixels along an axis, the cells it covers, None if it is off the board.
+// A pixel covers at least the cell under its top-left corner.
+fn pixel_spans(pixels: usize, cells: usize, to_board: impl Fn(f64) -> f64) -> Vec<Option<std::ops::Range<usize>>> {
+    (0..pixels)
+        .map(|pixel| {
+            let start = to_board(pixel as f64).floor();
+            let end = to_board(pixel as f64 + 1.0).floor().max(start + 1.0);
+            if start < 0.0 || start >= cells as f64 {
+                return None;
+            }
+            Some(start as usize..(end as usize).min(cells))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::cell_state::CellState;
+    use crate::render::theme::Theme;
+
+    fn board_with_cells(cells: &[(usize, usize)]) -> Board {
+        let mut board = Board::new(4, 4);
+        for (x, y) in cells {
+            board.set_cell(*x, *y, CellState::Alive);
+        }
+        board
+    }
+
+    #[test]
+    fn test_draw_board_scaled_up() {
+        let board = board_with_cells(&[(1, 0)]);
+        let palette = Theme::Light.palette();
+        let mut frame = Frame::new(10, 10);
+        frame.draw_board(&board, &Camera::new(10.0, 10.0, 2.0), &palette, ColorMode::Plain);
+
+        assert_eq!(frame.pixel(0, 0), palette.dead);
+        assert_eq!(frame.pixel(2, 0), palette.alive);
+        assert_eq!(frame.pixel(3, 1), palette.alive);
+        assert_eq!(frame.pixel(4, 0), palette.dead);
+        assert_eq!(frame.pixel(8, 8), palette.outside);
+    }
+
+    #[test]
+    fn test_draw_board_zoomed_out_shades_by_density() {
+        let board = board_with_cells(&[(0, 0), (1, 1), (2, 2)]);
+        let palette = Theme::Light.palette();
+        let mut frame = Frame::new(2, 2);
+        frame.draw_board(&board, &Camera::new(2.0, 2.0, 0.5), &palette, ColorMode::Plain);
+
+        assert_eq!(frame.pixel(0, 0), [128, 128, 128, 255]);
+        assert_eq!(frame.pixel(1, 1), [191, 191, 191, 255]);
+        assert_eq!(frame.pixel(1, 0), palette.dead);
+    }
+
+    #[test]
+    fn test_resize_and_set_pixel() {
+        let mut frame = Frame::new(2, 2);
+        frame.set_pixel(1, 1, [1, 2, 3, 4]);
+        frame.set_pixel(5, 5, [1, 2, 3, 4]);
+        assert_eq!(frame.pixel(1, 1), [1, 2, 3, 4]);
+
+        frame.fill_rect(1, 0, 5, 5, [255, 255, 255, 255]);
+        assert_eq!(frame.pixel(1, 1), [255, 255, 255, 255]);
+        frame.fill_rect(0, 0, 1, 1, [255, 0, 0, 128]);
+        assert_eq!(frame.pixel(0, 0), [128, 0, 0, 128]);
+
+        frame.resize(3, 1);
+        assert_eq!((frame.width, frame.height, frame.pixels.len()), (3, 1, 12));
+    }
+}
@@ -0,0 +1,8 @@
+/// Mapping between board cells and screen pixels.
+pub mod camera;
+/// Pixel buffers the board is drawn into.
+pub mod frame;
+/// A small bitmap font to write on frames.
+pub mod font;
+/// Colour themes and colour modes.
+pub mod theme;
@@ -0,0 +1,230 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::game::board::Board;
+use crate::game::cell_state::CellState;
+use crate::render::frame::Rgba;
+
+/// Age from which cells get the `old` colour in [`ColorMode::Age`].
+pub const AGE_SPAN: u32 = 64;
+/// Number of generations a dead cell takes to fade back to the `dead` colour in [`ColorMode::Trail`].
+pub const TRAIL_LENGTH: u32 = 16;
+
+/// The colours used to draw a board.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Palette {
+    /// Colour of the live cells.
+    pub alive: Rgba,
+    /// Colour of the dead cells.
+    pub dead: Rgba,
+    /// Colour of the pixels outside the board.
+    pub outside: Rgba,
+    /// Colour of newborn cells in [`ColorMode::Age`].
+    pub young: Rgba,
+    /// Colour of cells that lived for [`AGE_SPAN`] generations in [`ColorMode::Age`].
+    pub old: Rgba,
+    /// Colour of cells that just died in [`ColorMode::Trail`].
+    pub trail: Rgba,
+    /// Colour of the lines between cells, when they are drawn.
+    pub grid: Rgba,
+}
+
+impl Palette {
+    /// Returns the colour of a cell of the board. The age and trail modes need the board to track ages,
+    /// see [`Board::set_age_tracking`], and otherwise colour the cells like [`ColorMode::Plain`].
+    pub fn cell_color(&self, board: &Board, x: usize, y: usize, mode: ColorMode) -> Rgba {
+        let alive = board.cells[x][y] == CellState::Alive;
+        match (mode, alive, board.age(x, y)) {
+            (ColorMode::Age, true, Some(age)) => mix(self.young, self.old, age.min(AGE_SPAN) as f64 / AGE_SPAN as f64),
+            (ColorMode::Trail, false, Some(age)) if age < TRAIL_LENGTH => mix(self.trail, self.dead, age as f64 / TRAIL_LENGTH as f64),
+            (_, true, _) => self.alive,
+            (_, false, _) => self.dead,
+        }
+    }
+}
+
+/// A named set of colours.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Theme {
+    /// Black cells on white.
+    #[default]
+    Light,
+    /// Light cells on a dark background.
+    Dark,
+    /// Amber cells on black, like an old monitor.
+    Amber,
+}
+
+impl Theme {
+    /// All the themes, in the order they are cycled through.
+    pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::Amber];
+
+    /// Returns the colours of the theme.
+    pub fn palette(&self) -> Palette {
+        match self {
+            Theme::Light => Palette {
+                alive: [0, 0, 0, 255],
+                dead: [255, 255, 255, 255],
+                outside: [153, 153, 153, 255],
+                young: [214, 39, 40, 255],
+                old: [31, 60, 140, 255],
+                trail: [120, 170, 255, 255],
+                grid: [204, 204, 204, 255],
+            },
+            Theme::Dark => Palette {
+                alive: [230, 230, 230, 255],
+                dead: [18, 18, 24, 255],
+                outside: [48, 48, 56, 255],
+                young: [255, 200, 60, 255],
+                old: [140, 40, 200, 255],
+                trail: [40, 90, 160, 255],
+                grid: [52, 52, 62, 255],
+            },
+            Theme::Amber => Palette {
+                alive: [255, 176, 0, 255],
+                dead: [20, 12, 0, 255],
+                outside: [45, 30, 5, 255],
+                young: [255, 230, 120, 255],
+                old: [180, 80, 0, 255],
+                trail: [110, 60, 0, 255],
+                grid: [60, 38, 4, 255],
+            },
+        }
+    }
+
+    /// Returns the theme after this one in [`Theme::ALL`], wrapping around.
+    pub fn next(&self) -> Theme {
+        let index = Theme::ALL.iter().position(|theme| theme == self).unwrap_or(0);
+        Theme::ALL[(index + 1) % Theme::ALL.len()]
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "light" => Ok(Theme::Light),
+            "dark" => Ok(Theme::Dark),
+            "amber" => Ok(Theme::Amber),
+            _ => Err(format!("Unknown theme '{}', expected one of: light, dark, amber", s)),
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Theme::Light => write!(f, "light"),
+            Theme::Dark => write!(f, "dark"),
+            Theme::Amber => write!(f, "amber"),
+        }
+    }
+}
+
+/// What the colour of a cell shows.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ColorMode {
+    /// Only whether the cell is alive.
+    #[default]
+    Plain,
+    /// How long a live cell has lived, from the `young` to the `old` colour.
+    Age,
+    /// Cells that died recently leave a trail fading to the `dead` colour.
+    Trail,
+}
+
+impl ColorMode {
+    /// All the colour modes, in the order they are cycled through.
+    pub const ALL: [ColorMode; 3] = [ColorMode::Plain, ColorMode::Age, ColorMode::Trail];
+
+    /// Returns whether the mode needs the board to track the age of its cells.
+    pub fn needs_ages(&self) -> bool {
+        *self != ColorMode::Plain
+    }
+
+    /// Returns the mode after this one in [`ColorMode::ALL`], wrapping around.
+    pub fn next(&self) -> ColorMode {
+        let index = ColorMode::ALL.iter().position(|mode| mode == self).unwrap_or(0);
+        ColorMode::ALL[(index + 1) % ColorMode::ALL.len()]
+    }
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "plain" => Ok(ColorMode::Plain),
+            "age" => Ok(ColorMode::Age),
+            "trail" => Ok(ColorMode::Trail),
+            _ => Err(format!("Unknown colour mode '{}', expected one of: plain, age, trail", s)),
+        }
+    }
+}
+
+impl fmt::Display for ColorMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorMode::Plain => write!(f, "plain"),
+            ColorMode::Age => write!(f, "age"),
+            ColorMode::Trail => write!(f, "trail"),
+        }
+    }
+}
+
+/// Blends two colours, `amount` going from 0 for `from` to 1 for `to`.
+pub fn mix(from: Rgba, to: Rgba, amount: f64) -> Rgba {
+    let mut color = from;
+    for (channel, target) in color.iter_mut().zip(to) {
+        *channel = (*channel as f64 + (target as f64 - *channel as f64) * amount).round() as u8;
+    }
+    color
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        for theme in Theme::ALL {
+            assert_eq!(theme.to_string().parse::<Theme>(), Ok(theme));
+        }
+        for mode in ColorMode::ALL {
+            assert_eq!(mode.to_string().parse::<ColorMode>(), Ok(mode));
+        }
+        assert!("sepia".parse::<Theme>().is_err());
+        assert_eq!(Theme::Amber.next(), Theme::Light);
+        assert_eq!(ColorMode::Plain.next(), ColorMode::Age);
+    }
+
+    #[test]
+    fn test_age_and_trail_colors() {
+        let palette = Theme::Light.palette();
+        let mut board = Board::new(3, 1);
+        board.set_cell(0, 0, CellState::Alive);
+        board.set_cell(1, 0, CellState::Alive);
+        assert_eq!(palette.cell_color(&board, 0, 0, ColorMode::Age), palette.alive);
+
+        board.set_age_tracking(true);
+        assert_eq!(palette.cell_color(&board, 0, 0, ColorMode::Age), palette.young);
+        assert_eq!(palette.cell_color(&board, 0, 0, ColorMode::Trail), palette.alive);
+        assert_eq!(palette.cell_color(&board, 2, 0, ColorMode::Trail), palette.dead);
+
+        // Both cells die of loneliness, leaving a trail.
+        board.update();
+        assert_eq!(palette.cell_color(&board, 0, 0, ColorMode::Trail), palette.trail);
+        assert_eq!(palette.cell_color(&board, 0, 0, ColorMode::Plain), palette.dead);
+        for _ in 0..TRAIL_LENGTH {
+            board.update();
+        }
+        assert_eq!(palette.cell_color(&board, 0, 0, ColorMode::Trail), palette.dead);
+    }
+
+    #[test]
+    fn test_mix() {
+        assert_eq!(mix([0, 0, 0, 255], [255, 255, 255, 255], 0.5), [128, 128, 128, 255]);
+        assert_eq!(mix([10, 20, 30, 40], [0, 0, 0, 0], 0.0), [10, 20, 30, 40]);
+    }
+}
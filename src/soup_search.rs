@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use game_of_life::{object_name, separate_objects, Board, Census, CellState, PasteMode, Pattern, Rule, StabilityDetector, Transform};
+
+use crate::cli::Options;
+
+const SOUP_SIZE: usize = 16;
+const SOUP_DENSITY: f64 = 0.5;
+const DEFAULT_SOUPS: u64 = 1000;
+// The soups are run in the middle of a board of the first size, and again on the next size when they reach its edges.
+// Spaceships are removed and counted once they get near the edges, before they hit them.
+const BOARD_SIZES: [usize; 2] = [128, 256];
+const EDGE_MARGIN: usize = 12;
+const EDGE_CHECK_INTERVAL: u64 = 16;
+// Soups still active after this many generations are recorded as unsettled.
+const MAX_GENERATIONS: u64 = 20_000;
+const MAX_PERIOD: usize = 100;
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
+
+// Objects found in most searches, the soups holding any other object are recorded.
+const COMMON_OBJECTS: [&str; 14] = [
+    "xs4_33", "xp2_7", "xs6_696", "xq4_153", "xs7_2596", "xs5_253", "xs4_252", "xs6_356", "xs8_6996", "xs7_25ac",
+    "xp2_7e", "xp2_318c", "xs6_25a4", "xs12_g8o653z11",
+];
+
+const UNIDENTIFIED: &str = "unidentified";
+
+// The ash of one soup.
+#[derive(Debug)]
+struct Soup {
+    census: Census,
+    settled: bool,
+    // Whether the soup reached the edge of the largest board, which then changed how it evolved.
+    reached_edge: bool,
+}
+
+// The totals of a search, written to the results file and read back from it to resume the search.
+#[derive(PartialEq, Debug)]
+struct Results {
+    rule: Rule,
+    // Soup n is the 16x16 board filled with this seed plus n.
+    seed: u64,
+    soups: u64,
+    census: Census,
+    // The rare objects, by apgcode, and the seeds of the soups they were found in.
+    rare: Vec<(String, u64)>,
+    // The seeds of the soups that did not settle.
+    unsettled: Vec<u64>,
+    // The seeds of the soups that reached the edge of the board, left out of the census.
+    edge: Vec<u64>,
+}
+
+// Runs `options.soups` soups in total, 1000 by default, on as many threads as there are CPUs unless `options.threads`
+// is given, and writes the results to the search file every few seconds. An existing file is resumed where it stopped.
+pub fn run(filename: &str, options: &Options) -> Result<(), String> {
+    let mut results = match fs::read_to_string(filename) {
+        Ok(text) => {
+            let results: Results = text.parse().map_err(|message| format!("Cannot resume '{}': {}", filename, message))?;
+            if options.rule.is_some_and(|rule| rule != results.rule) || options.seed.is_some_and(|seed| seed != results.seed) {
+                return Err(format!("'{}' holds a search with rule {} and seed {}, which cannot be changed", filename, results.rule, results.seed));
+            }
+            eprintln!("Resuming the search of '{}' after {} soups", filename, results.soups);
+            results
+        }
+        Err(error) if error.kind() == ErrorKind::NotFound => {
+            Results::new(options.rule.unwrap_or_default(), options.seed.unwrap_or_else(rand::random))
+        }
+        Err(error) => return Err(format!("Cannot read '{}': {}", filename, error)),
+    };
+    let threads = options.threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |threads| threads.get()));
+
+    let start = Instant::now();
+    let first_soup = results.soups;
+    let next_soup = AtomicU64::new(first_soup);
+    let (sender, receiver) = mpsc::channel();
+    let (seed, rule, total) = (results.seed, results.rule, options.soups.unwrap_or(DEFAULT_SOUPS));
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let (sender, next_soup) = (sender.clone(), &next_soup);
+            scope.spawn(move || loop {
+                let index = next_soup.fetch_add(1, Ordering::Relaxed);
+                // The receiver is gone when the results could not be written.
+                if index >= total || sender.send((index, run_soup(seed.wrapping_add(index), rule))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // Soups finish out of order, only the soups up to the first unfinished one are recorded.
+        let mut finished = BTreeMap::new();
+        let mut last_checkpoint = Instant::now();
+        for (index, soup) in receiver {
+            finished.insert(index, soup);
+            while let Some(soup) = finished.remove(&results.soups) {
+                results.record(soup);
+            }
+            if last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+                write_results(&results, filename)?;
+                let rate = (results.soups - first_soup) as f64 / start.elapsed().as_secs_f64();
+                eprintln!("{} soups, {:.1} soups/s, {} rare objects", results.soups, rate, results.rare.len());
+                last_checkpoint = Instant::now();
+            }
+        }
+        Ok::<(), String>(())
+    })?;
+    write_results(&results, filename)?;
+
+    eprintln!("{} soups, {} unsettled, {} reaching the edge, {} rare objects", results.soups, results.unsettled.len(), results.edge.len(),
+              results.rare.len());
+    eprintln!("Census: {}", results.census);
+    Ok(())
+}
+
+// Replaces the file at once, so that an interrupted search leaves the previous results.
+fn write_results(results: &Results, filename: &str) -> Result<(), String> {
+    let temporary = format!("{}.tmp", filename);
+    fs::write(&temporary, results.to_string())
+        .and_then(|_| fs::rename(&temporary, filename))
+        .map_err(|error| format!("Cannot write '{}': {}", filename, error))
+}
+
+// Runs the soup of the given seed until it settles, removing and counting the spaceships that escape.
+fn run_soup(seed: u64, rule: Rule) -> Soup {
+    let mut soup = Board::new(SOUP_SIZE, SOUP_SIZE);
+    soup.set_seeded_initial_state(SOUP_DENSITY, seed);
+    let soup = soup.extract(0, 0, SOUP_SIZE, SOUP_SIZE);
+    let mut result = run_soup_on_board(&soup, rule, BOARD_SIZES[0]);
+    for &board_size in &BOARD_SIZES[1..] {
+        if !result.reached_edge {
+            break;
+        }
+        result = run_soup_on_board(&soup, rule, board_size);
+    }
+    result
+}
+
+fn run_soup_on_board(soup: &Pattern, rule: Rule, board_size: usize) -> Soup {
+    let mut board = Board::new(board_size, board_size);
+    board.rule = rule;
+    let corner = (board_size - SOUP_SIZE) / 2;
+    // The soup is smaller than the board.
+    board.place_pattern(soup, corner, corner, Transform::Identity, PasteMode::Or)
+        .expect("The soup does not fit in the board");
+
+    let mut census = Census::default();
+    let mut detector = StabilityDetector::new(MAX_PERIOD);
+    let mut reached_edge = false;
+    for generation in 0..MAX_GENERATIONS {
+        if detector.observe(&board).is_some() {
+            census.merge(&Census::of_board(&board));
+            return Soup { census, settled: true, reached_edge };
+        }
+        if generation % EDGE_CHECK_INTERVAL == 0 && remove_escaping_spaceships(&mut board, &mut census) {
+            detector.reset();
+        }
+        reached_edge = reached_edge || touches_edge(&board);
+        board.update();
+    }
+    Soup { census, settled: false, reached_edge }
+}
+
+fn touches_edge(board: &Board) -> bool {
+    let last = board.grid_width - 1;
+    (0..board.grid_width).any(|i| [(i, 0), (i, last), (0, i), (last, i)].iter().any(|&(x, y)| board.cells[x][y] == CellState::Alive))
+}
+
+fn remove_escaping_spaceships(board: &mut Board, census: &mut Census) -> bool {
+    // The board is square.
+    let size = board.grid_width;
+    let near_edge = |x: usize, y: usize| x.min(y) < EDGE_MARGIN || x.max(y) >= size - EDGE_MARGIN;
+    let edge_cells = (0..size).any(|x| (0..size).any(|y| near_edge(x, y) && board.cells[x][y] == CellState::Alive));
+    if !edge_cells {
+        return false;
+    }
+
+    let mut removed = false;
+    for object in separate_objects(board) {
+        let (left, top) = object.position;
+        let (right, bottom) = (left + object.pattern.width - 1, top + object.pattern.height - 1);
+        let spaceship = object.apgcode.as_deref().is_some_and(|apgcode| apgcode.starts_with("xq"));
+        if spaceship && (near_edge(left, top) || near_edge(right, bottom)) {
+            for x in 0..object.pattern.width {
+                for y in 0..object.pattern.height {
+                    if object.pattern.get_cell(x, y) == CellState::Alive {
+                        board.set_cell(left + x, top + y, CellState::Dead);
+                    }
+                }
+            }
+            census.add(&object);
+            removed = true;
+        }
+    }
+    removed
+}
+
+impl Results {
+    fn new(rule: Rule, seed: u64) -> Results {
+        Results { rule, seed, soups: 0, census: Census::default(), rare: Vec::new(), unsettled: Vec::new(), edge: Vec::new() }
+    }
+
+    // Adds the ash of the next soup.
+    fn record(&mut self, soup: Soup) {
+        let seed = self.seed.wrapping_add(self.soups);
+        self.soups += 1;
+        if !soup.settled {
+            self.unsettled.push(seed);
+            return;
+        }
+        if soup.reached_edge {
+            self.edge.push(seed);
+            return;
+        }
+        for (apgcode, _) in soup.census.iter().filter(|(apgcode, _)| !COMMON_OBJECTS.contains(apgcode)) {
+            self.rare.push((apgcode.to_string(), seed));
+        }
+        if soup.census.unidentified() > 0 {
+            self.rare.push((UNIDENTIFIED.to_string(), seed));
+        }
+        self.census.merge(&soup.census);
+    }
+}
+
+// One `keyword values...` line per entry, the common names of the objects are only there to be read:
+//
+//     rule B3/S23
+//     seed 42
+//     soups 1000
+//     count 24567 xs4_33 block
+//     rare 1276 xs7_178c eater
+//     unsettled 1290
+//     edge 1311
+impl std::fmt::Display for Results {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let with_name = |apgcode: &str| match object_name(apgcode) {
+            Some(name) => format!("{} {}", apgcode, name),
+            None => apgcode.to_string(),
+        };
+        writeln!(f, "# Soup search of {}x{} soups, run the same command again to resume it.", SOUP_SIZE, SOUP_SIZE)?;
+        writeln!(f, "rule {}", self.rule)?;
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "soups {}", self.soups)?;
+        for (apgcode, count) in self.census.iter() {
+            writeln!(f, "count {} {}", count, with_name(apgcode))?;
+        }
+        if self.census.unidentified() > 0 {
+            writeln!(f, "count {} {}", self.census.unidentified(), UNIDENTIFIED)?;
+        }
+        for (apgcode, seed) in &self.rare {
+            writeln!(f, "rare {} {}", seed, with_name(apgcode))?;
+        }
+        for seed in &self.unsettled {
+            writeln!(f, "unsettled {}", seed)?;
+        }
+        for seed in &self.edge {
+            writeln!(f, "edge {}", seed)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Results {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut rule = None;
+        let mut seed = None;
+        let mut results = Results::new(Rule::default(), 0);
+        for (line_number, line) in text.lines().enumerate() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let number = |index: usize| -> Result<u64, String> {
+                fields.get(index).and_then(|field| field.parse().ok()).ok_or_else(|| format!("Invalid line {}: '{}'", line_number + 1, line))
+            };
+            match fields.first().copied() {
+                None => {}
+                Some(keyword) if keyword.starts_with('#') => {}
+                Some("rule") => rule = Some(fields.get(1).copied().unwrap_or_default().parse::<Rule>()?),
+                Some("seed") => seed = Some(number(1)?),
+                Some("soups") => results.soups = number(1)?,
+                Some("count") => {
+                    let apgcode = fields.get(2).copied().filter(|&apgcode| apgcode != UNIDENTIFIED);
+                    results.census.add_count(apgcode, number(1)? as usize);
+                }
+                Some("rare") => results.rare.push((fields.get(2).copied().unwrap_or(UNIDENTIFIED).to_string(), number(1)?)),
+                Some("unsettled") => results.unsettled.push(number(1)?),
+                Some("edge") => results.edge.push(number(1)?),
+                Some(keyword) => return Err(format!("Unknown entry '{}' on line {}", keyword, line_number + 1)),
+            }
+        }
+        results.rule = rule.ok_or("The results have no rule")?;
+        results.seed = seed.ok_or("The results have no seed")?;
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soups_settle_into_the_same_ash_every_time() {
+        let soup = run_soup(1, Rule::conway());
+        assert!(soup.settled && !soup.reached_edge);
+        assert!(soup.census.total() > 0);
+        assert_eq!(run_soup(1, Rule::conway()).census, soup.census);
+    }
+
+    #[test]
+    fn escaping_gliders_are_counted_and_removed() {
+        let mut board = Board::new(BOARD_SIZES[0], BOARD_SIZES[0]);
+        let glider = game_of_life::parse_pattern(".X.\n..X\nXXX").unwrap();
+        board.place_pattern(&glider, 3, 60, Transform::Identity, PasteMode::Or).unwrap();
+        board.place_pattern(&game_of_life::parse_pattern("XX\nXX").unwrap(), 2, 2, Transform::Identity, PasteMode::Or).unwrap();
+        let mut census = Census::default();
+        assert!(remove_escaping_spaceships(&mut board, &mut census));
+        assert_eq!(census.to_string(), "1 glider");
+        assert_eq!(board.population(), 4);
+    }
+
+    #[test]
+    fn results_are_written_and_read_back() {
+        let mut results = Results::new("B36/S23".parse().unwrap(), 40);
+        let mut census = Census::default();
+        census.add_count(Some("xs4_33"), 3);
+        census.add_count(Some("xs7_178c"), 1);
+        census.add_count(None, 1);
+        results.record(Soup { census: census.clone(), settled: true, reached_edge: false });
+        results.record(Soup { census: Census::default(), settled: false, reached_edge: false });
+        results.record(Soup { census, settled: true, reached_edge: true });
+
+        assert_eq!(results.rare, vec![("xs7_178c".to_string(), 40), ("unidentified".to_string(), 40)]);
+        assert_eq!((results.unsettled.as_slice(), results.edge.as_slice()), ([41].as_slice(), [42].as_slice()));
+        assert_eq!((results.soups, results.census.total()), (3, 5));
+        let text = results.to_string();
+        assert!(text.contains("\ncount 3 xs4_33 block\n"), "{}", text);
+        assert!(text.contains("\nrare 40 xs7_178c eater\n"), "{}", text);
+        assert_eq!(text.parse::<Results>(), Ok(results));
+
+        assert!("seed 4\n".parse::<Results>().is_err());
+        assert!("rule B3/S23\nseed 4\nsoups many\n".parse::<Results>().is_err());
+        assert!("rule B3/S23\nseed 4\nprogress 3\n".parse::<Results>().is_err());
+    }
+}
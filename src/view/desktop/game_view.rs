@@ -1,43 +1,183 @@
-use crate::game::cell_state::CellState;
-use crate::game::game_state::GameState;
+use game_of_life::{write_snapshot, Camera, CameraMode, CellState, Frame, GameState, SnapshotFormat, SnapshotOptions, Transform};
+
+use crate::view::editor::Editor;
+use crate::view::game_controller::GameController;
+use crate::view::hud::Hud;
 use crate::view::game_view_trait::GameViewTrait;
 
-use opengl_graphics::GlGraphics;
-use graphics::{clear, rectangle};
+use opengl_graphics::{CreateTexture, Filter, Format, GlGraphics, Texture, TextureSettings, UpdateTexture};
+use graphics::{image, rectangle, ImageSize, Rectangle};
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::time::{Duration, Instant};
-use piston_window::{Events, EventSettings, OpenGL, PistonWindow, RenderArgs, RenderEvent, WindowSettings};
+use piston_window::{Button, Events, EventSettings, Key, MouseButton, MouseCursorEvent, MouseScrollEvent, OpenGL, PistonWindow, PressEvent, ReleaseEvent, RenderArgs, RenderEvent, Window, WindowSettings};
+
+const ZOOM_STEP: f64 = 1.25;
+const PAN_STEP: f64 = 32.0;
+// The largest window opened, in pixels. Larger boards are zoomed out to fit it.
+const MAX_WINDOW_SIZE: [u32; 2] = [1280, 800];
 
 pub struct GameView {
-    game_state: GameState,
+    controller: GameController,
+    editor: Editor,
+    camera: Camera,
     cell_size: usize,
-    update_interval: Duration,
+    control_pressed: bool,
+    panning: bool,
+    cursor_position: Option<[f64; 2]>,
+    frame: Frame,
+    texture: Option<Texture>,
+    hud: Hud,
+    start_time: Instant,
     gl: GlGraphics,
     window: PistonWindow,
 }
 
 impl GameView {
     fn render(&mut self, args: &RenderArgs) {
-        const ALIVE_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
-        const DEAD_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+        const SELECTION_COLOR: [f32; 4] = [0.1, 0.4, 0.9, 1.0];
+        const FLOATING_COLOR: [f32; 4] = [0.1, 0.6, 0.2, 0.6];
+
+        let board = &self.controller.game_state.board;
+        let [width, height] = args.window_size;
+        self.camera.resize(width, height);
+        self.camera.follow(board);
+
+        // The board is drawn on the CPU into the frame, which is uploaded as a single texture.
+        self.frame.resize(width as usize, height as usize);
+        let palette = self.controller.theme.palette();
+        self.frame.draw_board(board, &self.camera, &palette, self.controller.color_mode());
+        self.hud.observe(&self.controller.game_state, self.start_time.elapsed().as_secs_f64() * 1000.0);
+        self.hud.draw(&mut self.frame, &self.controller, &palette);
+        let size = [self.frame.width as u32, self.frame.height as u32];
+        let texture = match self.texture.take() {
+            Some(mut texture) if texture.get_size() == (size[0], size[1]) => {
+                UpdateTexture::update(&mut texture, &mut (), Format::Rgba8, &self.frame.pixels, [0, 0], size).map(|_| texture)
+            }
+            _ => Texture::create(&mut (), Format::Rgba8, &self.frame.pixels, size, &TextureSettings::new().filter(Filter::Nearest)),
+        };
+        self.texture = texture.ok();
+
+        let camera = &self.camera;
+        let zoom = camera.zoom;
+        let screen_rectangle = |x: usize, y: usize, width: usize, height: usize| {
+            let (left, top) = camera.to_screen(x as f64, y as f64);
+            [left, top, width as f64 * zoom, height as f64 * zoom]
+        };
 
         self.gl.draw(args.viewport(), |context, graphics| {
-            clear(DEAD_COLOR, graphics);
-
-            for (x, row) in self.game_state.board.cells.iter().enumerate() {
-                for (y, cell) in row.iter().enumerate() {
-                    let square = rectangle::square((x * self.cell_size) as f64, (y * self.cell_size) as f64, self.cell_size as f64);
-                    let color = match cell {
-                        CellState::Alive => ALIVE_COLOR,
-                        CellState::Dead => DEAD_COLOR,
-                    };
-                    rectangle(color, square, context.transform, graphics);
+            if let Some(texture) = &self.texture {
+                image(texture, context.transform, graphics);
+            }
+
+            if let Some((x, y, width, height)) = self.editor.selection().map(|selection| selection.bounds()) {
+                Rectangle::new_border(SELECTION_COLOR, 1.0).draw(screen_rectangle(x, y, width, height), &context.draw_state, context.transform, graphics);
+            }
+            if let Some((pattern, x, y)) = self.editor.floating(board) {
+                for (pattern_x, column) in pattern.cells.iter().enumerate() {
+                    for (pattern_y, cell) in column.iter().enumerate() {
+                        if *cell == CellState::Alive {
+                            rectangle(FLOATING_COLOR, screen_rectangle(x + pattern_x, y + pattern_y, 1, 1), context.transform, graphics);
+                        }
+                    }
                 }
             }
         });
     }
 
-    fn update(&mut self) {
-        self.game_state.update();
+    fn move_cursor(&mut self, position: [f64; 2]) {
+        if let Some(last_position) = self.cursor_position.filter(|_| self.panning) {
+            self.camera.pan(position[0] - last_position[0], position[1] - last_position[1]);
+        }
+        self.cursor_position = Some(position);
+        let cell = self.camera.cell_at(&self.controller.game_state.board, position[0], position[1]);
+        self.editor.move_cursor(&mut self.controller, cell);
+    }
+
+    fn zoom(&mut self, factor: f64) {
+        let [x, y] = self.cursor_position.unwrap_or([self.camera.viewport.0 / 2.0, self.camera.viewport.1 / 2.0]);
+        self.camera.zoom_at(factor, x, y);
+    }
+
+    fn handle_button(&mut self, button: Button, pressed: bool) {
+        match (button, pressed) {
+            (Button::Mouse(MouseButton::Left), true) => self.editor.press_primary(&mut self.controller),
+            (Button::Mouse(MouseButton::Left), false) => self.editor.release_primary(),
+            (Button::Mouse(MouseButton::Right), true) => self.editor.press_secondary(),
+            (Button::Mouse(MouseButton::Right), false) => self.editor.release_secondary(),
+            (Button::Mouse(MouseButton::Middle), pressed) => self.panning = pressed,
+            (Button::Keyboard(Key::LCtrl | Key::RCtrl | Key::LGui | Key::RGui), pressed) => self.control_pressed = pressed,
+            (Button::Keyboard(key), true) if self.control_pressed => self.handle_control_key(key),
+            (Button::Keyboard(key), true) => self.handle_key(key),
+            _ => {}
+        }
+    }
+
+    fn handle_control_key(&mut self, key: Key) {
+        match key {
+            Key::C => self.editor.copy(&mut self.controller),
+            Key::X => self.editor.cut(&mut self.controller),
+            Key::V => self.editor.paste(&self.controller),
+            Key::S => self.save_snapshots(),
+            _ => {}
+        }
+    }
+
+    // Writes the board as generation-N.png and generation-N.svg in the working directory, with the current colours,
+    // and tells how it went on the HUD.
+    fn save_snapshots(&mut self) {
+        let options = SnapshotOptions {
+            cell_size: self.cell_size,
+            palette: self.controller.theme.palette(),
+            color_mode: self.controller.color_mode(),
+            ..SnapshotOptions::default()
+        };
+        let mut saved = Vec::new();
+        for format in [SnapshotFormat::Png, SnapshotFormat::Svg] {
+            let filename = format!("generation-{}.{}", self.controller.game_state.generation, format);
+            let result = File::create(&filename).and_then(|file| {
+                let mut writer = BufWriter::new(file);
+                write_snapshot(&self.controller.game_state.board, format, &options, &mut writer)?;
+                writer.flush()
+            });
+            if let Err(error) = result {
+                self.hud.notify(format!("Cannot write {}: {}", filename, error));
+                return;
+            }
+            saved.push(filename);
+        }
+        self.hud.notify(format!("Saved {}", saved.join(" and ")));
+    }
+
+    fn handle_key(&mut self, key: Key) {
+        match key {
+            // Escape first drops the floating pattern or the selection, and closes the window when there is none.
+            Key::Escape if !self.editor.cancel() => self.window.set_should_close(true),
+            Key::Delete | Key::Backspace => self.editor.clear(&mut self.controller),
+            Key::F => self.editor.randomize(&mut self.controller),
+            Key::LeftBracket => self.editor.transform_floating(Transform::Rotate270),
+            Key::RightBracket => self.editor.transform_floating(Transform::Rotate90),
+            Key::H => self.editor.transform_floating(Transform::FlipHorizontal),
+            Key::V => self.editor.transform_floating(Transform::FlipVertical),
+            Key::I | Key::PageUp => self.zoom(ZOOM_STEP),
+            Key::O | Key::PageDown => self.zoom(1.0 / ZOOM_STEP),
+            Key::Left => self.camera.pan(PAN_STEP, 0.0),
+            Key::Right => self.camera.pan(-PAN_STEP, 0.0),
+            Key::Up => self.camera.pan(0.0, PAN_STEP),
+            Key::Down => self.camera.pan(0.0, -PAN_STEP),
+            Key::Home => self.camera.toggle_mode(CameraMode::FitPattern),
+            Key::C => self.camera.toggle_mode(CameraMode::FollowCentroid),
+            Key::T => self.controller.theme = self.controller.theme.next(),
+            Key::A => self.controller.set_color_mode(self.controller.color_mode().next()),
+            Key::Tab => self.hud.toggle(),
+            Key::Space => self.toggle_pause(),
+            Key::N | Key::Period => self.step(),
+            Key::M => self.step_n(10),
+            Key::Plus | Key::Equals | Key::NumPadPlus => self.controller.faster(),
+            Key::Minus | Key::NumPadMinus => self.controller.slower(),
+            Key::R => self.reset(),
+            _ => {}
+        }
     }
 }
 
@@ -45,21 +185,32 @@ impl GameViewTrait for GameView {
     fn new(game_state: GameState, cell_size: usize, update_interval_ms: usize) -> Self {
         let opengl = OpenGL::V3_2;
 
-        let size = [
-            (game_state.board.grid_width * cell_size) as u32,
-            (game_state.board.grid_height * cell_size) as u32
-        ];
+        let board = &game_state.board;
+        let board_size = [board.grid_width.saturating_mul(cell_size), board.grid_height.saturating_mul(cell_size)];
+        let size = [0, 1].map(|axis| board_size[axis].min(MAX_WINDOW_SIZE[axis] as usize) as u32);
+        let mut camera = Camera::new(size[0] as f64, size[1] as f64, cell_size as f64);
+        if board_size[0] > size[0] as usize || board_size[1] > size[1] as usize {
+            camera.fit(0, 0, board.grid_width, board.grid_height);
+        }
 
         let window: PistonWindow = WindowSettings::new("Game of Life", size)
             .graphics_api(opengl)
-            .exit_on_esc(true)
+            .exit_on_esc(false)
             .build()
             .unwrap();
 
         Self {
-            game_state,
+            controller: GameController::new(game_state, Duration::from_millis(update_interval_ms as u64)),
+            editor: Editor::default(),
+            camera,
             cell_size,
-            update_interval: Duration::from_millis(update_interval_ms as u64),
+            control_pressed: false,
+            panning: false,
+            cursor_position: None,
+            frame: Frame::new(size[0] as usize, size[1] as usize),
+            texture: None,
+            hud: Hud::default(),
+            start_time: Instant::now(),
             gl: GlGraphics::new(opengl),
             window,
         }
@@ -74,11 +225,32 @@ impl GameViewTrait for GameView {
                 self.render(&r);
             }
 
-            if last_update.elapsed() >= self.update_interval {
-                self.update();
+            if let Some(position) = e.mouse_cursor_args() {
+                self.move_cursor(position);
+            }
+
+            // Horizontal-only scrolls, as on trackpads, do not zoom.
+            if let Some([_, scroll]) = e.mouse_scroll_args().filter(|[_, scroll]| *scroll != 0.0) {
+                self.zoom(ZOOM_STEP.powf(scroll.signum()));
+            }
+
+            if let Some(button) = e.press_args() {
+                self.handle_button(button, true);
+            }
+
+            if let Some(button) = e.release_args() {
+                self.handle_button(button, false);
+            }
+
+            if last_update.elapsed() >= self.controller.update_interval() {
+                self.controller.tick();
                 last_update = Instant::now();
             }
         }
         Ok(())
     }
+
+    fn controller(&mut self) -> &mut GameController {
+        &mut self.controller
+    }
 }
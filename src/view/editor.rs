@@ -0,0 +1,234 @@
+use game_of_life::{Board, CellState, PasteMode, Pattern, Transform};
+
+use crate::view::game_controller::GameController;
+
+const RANDOMIZE_ALIVE_PROBABILITY: f64 = 0.5;
+
+// A rectangle of cells spanned by two opposite corners, given in any order.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Selection {
+    anchor: (usize, usize),
+    corner: (usize, usize),
+}
+
+// The web view only draws with the editor, selections and the clipboard are driven by the keys of the desktop view.
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+impl Selection {
+    // Returns the selected region as (x, y, width, height).
+    pub fn bounds(&self) -> (usize, usize, usize, usize) {
+        let (x, y) = (self.anchor.0.min(self.corner.0), self.anchor.1.min(self.corner.1));
+        let width = self.anchor.0.abs_diff(self.corner.0) + 1;
+        let height = self.anchor.1.abs_diff(self.corner.1) + 1;
+        (x, y, width, height)
+    }
+}
+
+// Mouse editing shared by the views: drawing and erasing cells, selecting a region,
+// and stamping the clipboard under the cursor. Coordinates are in cells.
+#[derive(Clone, Default)]
+pub struct Editor {
+    cursor: Option<(usize, usize)>,
+    // The state painted while the drawing button is held, the opposite of the first cell clicked.
+    brush: Option<CellState>,
+    selecting: bool,
+    selection: Option<Selection>,
+    // The clipboard, possibly rotated or flipped, following the cursor until it is stamped.
+    floating: Option<Pattern>,
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+impl Editor {
+    pub fn selection(&self) -> Option<Selection> {
+        self.selection
+    }
+
+    // Returns the floating pattern and the top-left cell it would be stamped at.
+    pub fn floating(&self, board: &Board) -> Option<(&Pattern, usize, usize)> {
+        let pattern = self.floating.as_ref()?;
+        let (x, y) = self.cursor?;
+        let x = x.saturating_sub(pattern.width / 2).min(board.grid_width.saturating_sub(pattern.width));
+        let y = y.saturating_sub(pattern.height / 2).min(board.grid_height.saturating_sub(pattern.height));
+        Some((pattern, x, y))
+    }
+
+    // `cursor` is None when the pointer leaves the board.
+    pub fn move_cursor(&mut self, controller: &mut GameController, cursor: Option<(usize, usize)>) {
+        if let (Some(brush), Some(from), Some(to)) = (self.brush, self.cursor, cursor) {
+            paint_line(&mut controller.game_state.board, from, to, brush);
+        }
+        if let (true, Some(selection), Some(corner)) = (self.selecting, self.selection.as_mut(), cursor) {
+            selection.corner = corner;
+        }
+        if cursor.is_some() || self.brush.is_none() {
+            self.cursor = cursor;
+        }
+    }
+
+    // Starts drawing, or stamps the floating pattern if there is one.
+    pub fn press_primary(&mut self, controller: &mut GameController) {
+        let board = &mut controller.game_state.board;
+        if let Some((pattern, x, y)) = self.floating(board) {
+            // The pattern is larger than the board if it does not fit, there is nothing sensible to stamp.
+            let _ = board.place_pattern(pattern, x, y, Transform::Identity, PasteMode::Or);
+            return;
+        }
+        if let Some((x, y)) = self.cursor {
+            let brush = match board.get_cell(x, y) {
+                CellState::Alive => CellState::Dead,
+                CellState::Dead => CellState::Alive,
+            };
+            board.set_cell(x, y, brush);
+            self.brush = Some(brush);
+        }
+    }
+
+    pub fn release_primary(&mut self) {
+        self.brush = None;
+    }
+
+    // Starts a new selection, or drops the floating pattern if there is one.
+    pub fn press_secondary(&mut self) {
+        if self.floating.take().is_some() {
+            return;
+        }
+        self.selection = self.cursor.map(|cursor| Selection { anchor: cursor, corner: cursor });
+        self.selecting = self.selection.is_some();
+    }
+
+    pub fn release_secondary(&mut self) {
+        self.selecting = false;
+    }
+
+    pub fn copy(&mut self, controller: &mut GameController) {
+        if let Some((x, y, width, height)) = self.selection.map(|selection| selection.bounds()) {
+            controller.clipboard = Some(controller.game_state.board.extract(x, y, width, height));
+        }
+    }
+
+    pub fn cut(&mut self, controller: &mut GameController) {
+        self.copy(controller);
+        self.clear(controller);
+    }
+
+    pub fn clear(&mut self, controller: &mut GameController) {
+        if let Some((x, y, width, height)) = self.selection.map(|selection| selection.bounds()) {
+            controller.game_state.board.fill_region(x, y, width, height, CellState::Dead);
+        }
+    }
+
+    pub fn randomize(&mut self, controller: &mut GameController) {
+        if let Some((x, y, width, height)) = self.selection.map(|selection| selection.bounds()) {
+            controller.game_state.board.randomize_region(x, y, width, height, RANDOMIZE_ALIVE_PROBABILITY);
+        }
+    }
+
+    // Makes the clipboard follow the cursor, the next primary click stamps it.
+    pub fn paste(&mut self, controller: &GameController) {
+        if let Some(clipboard) = &controller.clipboard {
+            self.floating = Some(clipboard.clone());
+            self.selection = None;
+        }
+    }
+
+    pub fn transform_floating(&mut self, transform: Transform) {
+        if let Some(floating) = &mut self.floating {
+            *floating = floating.transformed(transform);
+        }
+    }
+
+    // Drops the floating pattern or the selection, returns false when there was nothing to cancel.
+    pub fn cancel(&mut self) -> bool {
+        if self.floating.take().is_some() {
+            return true;
+        }
+        self.selecting = false;
+        self.selection.take().is_some()
+    }
+}
+
+// Sets the cells on the segment between `from` and `to`, so that fast strokes leave no gaps.
+fn paint_line(board: &mut Board, from: (usize, usize), to: (usize, usize), cell_state: CellState) {
+    let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+    let (to_x, to_y) = (to.0 as isize, to.1 as isize);
+    let (dx, dy) = ((to_x - x).abs(), -(to_y - y).abs());
+    let (step_x, step_y) = ((to_x - x).signum(), (to_y - y).signum());
+    let mut error = dx + dy;
+
+    loop {
+        board.set_cell(x as usize, y as usize, cell_state);
+        if (x, y) == (to_x, to_y) {
+            return;
+        }
+        let double_error = 2 * error;
+        if double_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if double_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use game_of_life::GameState;
+    use std::time::Duration;
+
+    fn controller() -> GameController {
+        GameController::new(GameState::new(10, 10, 1), Duration::from_millis(100))
+    }
+
+    #[test]
+    fn test_selection_bounds() {
+        let selection = Selection { anchor: (5, 1), corner: (2, 3) };
+        assert_eq!(selection.bounds(), (2, 1, 4, 3));
+    }
+
+    #[test]
+    fn test_draw_and_erase_stroke() {
+        let mut controller = controller();
+        let mut editor = Editor::default();
+
+        editor.move_cursor(&mut controller, Some((1, 1)));
+        editor.press_primary(&mut controller);
+        editor.move_cursor(&mut controller, Some((4, 3)));
+        editor.release_primary();
+        editor.move_cursor(&mut controller, Some((8, 8)));
+        assert_eq!(controller.game_state.board.population(), 4);
+        assert_eq!(controller.game_state.board.bounding_box(), Some((1, 1, 4, 3)));
+
+        editor.move_cursor(&mut controller, Some((1, 1)));
+        editor.press_primary(&mut controller);
+        editor.move_cursor(&mut controller, Some((4, 3)));
+        editor.release_primary();
+        assert_eq!(controller.game_state.board.population(), 0);
+    }
+
+    #[test]
+    fn test_cut_and_paste_selection() {
+        let mut controller = controller();
+        let mut editor = Editor::default();
+        controller.game_state.board.set_cell(1, 1, CellState::Alive);
+        controller.game_state.board.set_cell(2, 1, CellState::Alive);
+
+        editor.move_cursor(&mut controller, Some((1, 1)));
+        editor.press_secondary();
+        editor.move_cursor(&mut controller, Some((2, 2)));
+        editor.release_secondary();
+        editor.cut(&mut controller);
+        assert_eq!(controller.game_state.board.population(), 0);
+
+        editor.paste(&controller);
+        editor.transform_floating(Transform::Rotate90);
+        editor.move_cursor(&mut controller, Some((9, 9)));
+        editor.press_primary(&mut controller);
+        assert_eq!(controller.game_state.board.bounding_box(), Some((9, 8, 1, 2)));
+
+        assert!(editor.cancel());
+        assert!(!editor.cancel());
+    }
+}
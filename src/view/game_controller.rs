@@ -0,0 +1,164 @@
+use game_of_life::{Board, ColorMode, GameState, Pattern, Theme, Topology};
+
+use std::time::Duration;
+
+pub const MIN_UPDATE_INTERVAL: Duration = Duration::from_millis(10);
+pub const MAX_UPDATE_INTERVAL: Duration = Duration::from_secs(5);
+
+// Playback state shared by the views: whether the game runs, how fast, the state to reset to,
+// the clipboard the editing views paste from, and how the board is coloured.
+#[derive(Clone)]
+pub struct GameController {
+    pub game_state: GameState,
+    pub clipboard: Option<Pattern>,
+    pub theme: Theme,
+    // Set for boards whose size is part of the game, such as those of shared links, to keep it when the view resizes.
+    pub keep_board_size: bool,
+    color_mode: ColorMode,
+    initial_state: GameState,
+    paused: bool,
+    update_interval: Duration,
+}
+
+impl GameController {
+    pub fn new(game_state: GameState, update_interval: Duration) -> Self {
+        GameController {
+            initial_state: game_state.clone(),
+            game_state,
+            clipboard: None,
+            theme: Theme::default(),
+            keep_board_size: false,
+            color_mode: ColorMode::default(),
+            paused: false,
+            update_interval: update_interval.clamp(MIN_UPDATE_INTERVAL, MAX_UPDATE_INTERVAL),
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn play(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn update_interval(&self) -> Duration {
+        self.update_interval
+    }
+
+    pub fn set_update_interval(&mut self, update_interval: Duration) {
+        self.update_interval = update_interval.clamp(MIN_UPDATE_INTERVAL, MAX_UPDATE_INTERVAL);
+    }
+
+    // Keyboard shortcuts of the native views, the web page sets the speed directly.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    pub fn faster(&mut self) {
+        self.set_update_interval(self.update_interval / 2);
+    }
+
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    pub fn slower(&mut self) {
+        self.set_update_interval(self.update_interval * 2);
+    }
+
+    // Called by the views every `update_interval`, advances one generation unless paused.
+    pub fn tick(&mut self) -> bool {
+        if self.paused {
+            return false;
+        }
+        self.game_state.update();
+        true
+    }
+
+    // Pauses the game and advances it by `generations` generations.
+    pub fn step_n(&mut self, generations: u64) {
+        self.paused = true;
+        self.game_state.advance(generations);
+    }
+
+    // Replaces the game by a new one, which `reset` then goes back to.
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    pub fn restart(&mut self, game_state: GameState) {
+        self.initial_state = game_state.clone();
+        self.game_state = game_state;
+        self.game_state.board.set_age_tracking(self.color_mode.needs_ages());
+    }
+
+    // Resizes the board and the one `reset` goes back to, returns how far the live cells moved, see `Board::resize`.
+    //
+    // Neither board shrinks below its live cells, which would drop some of them. Tori keep their size, which changes
+    // how they evolve, and so do boards with `keep_board_size`.
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    pub fn resize_board(&mut self, grid_width: usize, grid_height: usize) -> (isize, isize) {
+        if self.keep_board_size || self.game_state.board.topology == Topology::Torus {
+            return (0, 0);
+        }
+        let live_size = |board: &Board| board.bounding_box().map_or((0, 0), |(_, _, width, height)| (width, height));
+        let (width, height) = live_size(&self.game_state.board);
+        let (initial_width, initial_height) = live_size(&self.initial_state.board);
+        let (grid_width, grid_height) = (grid_width.max(width).max(initial_width), grid_height.max(height).max(initial_height));
+        self.initial_state.board.resize(grid_width, grid_height);
+        self.game_state.board.resize(grid_width, grid_height)
+    }
+
+    pub fn reset(&mut self) {
+        self.game_state = self.initial_state.clone();
+        self.game_state.board.set_age_tracking(self.color_mode.needs_ages());
+    }
+
+    // The terminal view does not colour the board.
+    #[cfg_attr(not(any(feature = "desktop", feature = "web")), allow(dead_code))]
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    // The age and trail modes make the board track the age of its cells, which costs a little at each generation.
+    pub fn set_color_mode(&mut self, color_mode: ColorMode) {
+        self.color_mode = color_mode;
+        self.game_state.board.set_age_tracking(color_mode.needs_ages());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life::{parse_pattern, Placement};
+
+    fn glider_controller(topology: Topology) -> GameController {
+        let mut game_state = GameState::new(20, 20, 1);
+        game_state.set_topology(topology);
+        let glider = parse_pattern("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+        game_state.add_pattern(&glider, &Placement { position: Some((2, 3)), ..Placement::default() }).unwrap();
+        GameController::new(game_state, Duration::from_millis(100))
+    }
+
+    #[test]
+    fn test_resizing_keeps_the_live_cells() {
+        let mut controller = glider_controller(Topology::Bounded);
+        controller.step_n(8);
+        controller.resize_board(1, 2);
+        assert_eq!((controller.game_state.board.grid_width, controller.game_state.board.grid_height), (3, 3));
+        controller.resize_board(40, 30);
+        assert_eq!((controller.game_state.board.grid_width, controller.game_state.board.grid_height), (40, 30));
+        assert_eq!(controller.game_state.board.population(), 5);
+        controller.reset();
+        assert_eq!(controller.game_state.board.population(), 5);
+    }
+
+    #[test]
+    fn test_tori_and_shared_boards_keep_their_size() {
+        let mut controller = glider_controller(Topology::Torus);
+        assert_eq!(controller.resize_board(40, 30), (0, 0));
+        assert_eq!((controller.game_state.board.grid_width, controller.game_state.board.grid_height), (20, 20));
+
+        let mut controller = glider_controller(Topology::Bounded);
+        controller.keep_board_size = true;
+        controller.resize_board(40, 30);
+        assert_eq!(controller.game_state.board.bounding_box(), Some((2, 3, 3, 3)));
+        assert_eq!(controller.game_state.board.grid_width, 20);
+    }
+}
@@ -1,6 +1,47 @@
-use crate::game::game_state::GameState;
+use game_of_life::GameState;
+
+use crate::view::game_controller::GameController;
+
+use std::time::Duration;
 
 pub trait GameViewTrait {
     fn new(game_state: GameState, cell_size: usize, update_interval_ms: usize) -> Self where Self: Sized;
     fn init(&mut self) -> Result<(), String>;
-}
\ No newline at end of file
+    fn controller(&mut self) -> &mut GameController;
+
+    fn play(&mut self) {
+        self.controller().play();
+    }
+
+    fn pause(&mut self) {
+        self.controller().pause();
+    }
+
+    fn toggle_pause(&mut self) {
+        if self.controller().is_paused() {
+            self.play();
+        } else {
+            self.pause();
+        }
+    }
+
+    // The web view steps through `step_n`.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    fn step(&mut self) {
+        self.step_n(1);
+    }
+
+    fn step_n(&mut self, generations: u64) {
+        self.controller().step_n(generations);
+    }
+
+    // Only the web view exposes an absolute speed, the native views use `GameController::faster` and `slower`.
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    fn set_speed(&mut self, update_interval_ms: usize) {
+        self.controller().set_update_interval(Duration::from_millis(update_interval_ms as u64));
+    }
+
+    fn reset(&mut self) {
+        self.controller().reset();
+    }
+}
@@ -0,0 +1,123 @@
+use game_of_life::render::font::GLYPH_HEIGHT;
+use game_of_life::{text_width, Frame, GameState, Palette, Stability, StabilityDetector};
+
+use crate::view::game_controller::GameController;
+
+use std::collections::VecDeque;
+
+const MAX_PERIOD: usize = 100;
+// Generations-per-second are measured over this window, in milliseconds.
+const SPEED_WINDOW_MS: f64 = 2000.0;
+const SCALE: usize = 2;
+const MARGIN: usize = 4;
+const PANEL_ALPHA: u8 = 200;
+// How long a notice stays on screen, in milliseconds.
+const NOTICE_MS: f64 = 4000.0;
+
+// On-screen information about the run, drawn over the board by the graphical views.
+#[derive(Clone)]
+pub struct Hud {
+    pub visible: bool,
+    detector: StabilityDetector,
+    stability: Option<Stability>,
+    last_generation: Option<u64>,
+    // (generation, time in milliseconds) of the recent generations.
+    samples: VecDeque<(u64, f64)>,
+    // A message about the last action and the time until which it is shown, in milliseconds.
+    notice: Option<(String, f64)>,
+    now_ms: f64,
+}
+
+impl Default for Hud {
+    fn default() -> Self {
+        Hud {
+            visible: true,
+            detector: StabilityDetector::new(MAX_PERIOD),
+            stability: None,
+            last_generation: None,
+            samples: VecDeque::new(),
+            notice: None,
+            now_ms: 0.0,
+        }
+    }
+}
+
+impl Hud {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    // To be called before each frame, `now_ms` being a monotonic time in milliseconds.
+    pub fn observe(&mut self, game_state: &GameState, now_ms: f64) {
+        self.now_ms = now_ms;
+        if self.last_generation == Some(game_state.generation) {
+            return;
+        }
+        // Periods can only be detected from consecutive generations, so steps, resets and edits between
+        // generations start the detection again.
+        if self.last_generation.map(|generation| generation + 1) != Some(game_state.generation) {
+            self.detector.reset();
+            self.samples.clear();
+        }
+        self.stability = self.detector.observe(&game_state.board);
+        self.last_generation = Some(game_state.generation);
+
+        self.samples.push_back((game_state.generation, now_ms));
+        while self.samples.front().is_some_and(|(_, time)| now_ms - time > SPEED_WINDOW_MS) {
+            self.samples.pop_front();
+        }
+    }
+
+    // Shows a message for a few seconds, even when the rest of the display is hidden.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    pub fn notify(&mut self, message: String) {
+        self.notice = Some((message, self.now_ms + NOTICE_MS));
+    }
+
+    // Returns the measured number of generations per second, if enough generations were seen.
+    fn speed(&self) -> Option<f64> {
+        let ((first_generation, first_time), (last_generation, last_time)) = (self.samples.front()?, self.samples.back()?);
+        (last_time > first_time).then(|| (last_generation - first_generation) as f64 * 1000.0 / (last_time - first_time))
+    }
+
+    pub fn lines(&self, controller: &GameController) -> Vec<String> {
+        let game_state = &controller.game_state;
+        let speed = if controller.is_paused() {
+            "Paused".to_string()
+        } else {
+            let target = 1.0 / controller.update_interval().as_secs_f64();
+            match self.speed() {
+                Some(speed) => format!("{:.1} / {:.1} gen/s", speed, target),
+                None => format!("- / {:.1} gen/s", target),
+            }
+        };
+        let state = self.stability.map_or("Evolving".to_string(), |stability| stability.to_string());
+
+        vec![
+            format!("Generation {}", game_state.generation),
+            format!("Population {}", game_state.board.population()),
+            format!("Rule {}", game_state.board.rule),
+            speed,
+            state,
+        ]
+    }
+
+    pub fn draw(&self, frame: &mut Frame, controller: &GameController, palette: &Palette) {
+        let mut lines = if self.visible { self.lines(controller) } else { Vec::new() };
+        if let Some((message, _)) = self.notice.as_ref().filter(|(_, until)| self.now_ms < *until) {
+            lines.push(message.clone());
+        }
+        if lines.is_empty() {
+            return;
+        }
+        let line_height = (GLYPH_HEIGHT + 3) * SCALE;
+        let width = lines.iter().map(|line| text_width(line, SCALE)).max().unwrap_or(0) + 2 * MARGIN;
+        let height = lines.len() * line_height + 2 * MARGIN - 3 * SCALE;
+
+        let [red, green, blue, _] = palette.dead;
+        frame.fill_rect(0, 0, width, height, [red, green, blue, PANEL_ALPHA]);
+        for (index, line) in lines.iter().enumerate() {
+            frame.draw_text(MARGIN, MARGIN + index * line_height, line, palette.alive, SCALE);
+        }
+    }
+}
@@ -1,19 +1,25 @@
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(feature = "desktop", not(target_arch = "wasm32")))]
 mod desktop;
 
-#[cfg(target_arch = "wasm32")]
-mod web;
+#[cfg(all(feature = "terminal", not(target_arch = "wasm32")))]
+pub mod terminal;
+
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub mod web;
 
 pub mod game_view {
-    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg(all(feature = "desktop", not(target_arch = "wasm32")))]
     pub use super::desktop::game_view::GameView;
 
-    #[cfg(target_arch = "wasm32")]
+    #[cfg(all(feature = "web", target_arch = "wasm32"))]
     pub use super::web::game_view::GameView;
 }
 
-pub mod game_view_trait;
-
-
+#[cfg(any(all(feature = "desktop", not(target_arch = "wasm32")), all(feature = "web", target_arch = "wasm32")))]
+pub mod editor;
 
+pub mod game_controller;
 
+#[cfg(any(all(feature = "desktop", not(target_arch = "wasm32")), all(feature = "web", target_arch = "wasm32")))]
+pub mod hud;
+pub mod game_view_trait;
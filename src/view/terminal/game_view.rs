@@ -0,0 +1,225 @@
+use game_of_life::{Board, CellState, GameState};
+
+use crate::view::game_controller::GameController;
+use crate::view::game_view_trait::GameViewTrait;
+
+use std::io::{self, stdout, Stdout, Write};
+use std::panic;
+use std::time::{Duration, Instant};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, size, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+const PAN_STEP: usize = 8;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Glyphs {
+    // 2x4 cells per character.
+    Braille,
+    // 1x2 cells per character.
+    HalfBlock,
+}
+
+impl Glyphs {
+    fn cells_per_char(&self) -> (usize, usize) {
+        match self {
+            Glyphs::Braille => (2, 4),
+            Glyphs::HalfBlock => (1, 2),
+        }
+    }
+
+    fn char_at(&self, board: &Board, x: usize, y: usize) -> char {
+        let alive = |dx: usize, dy: usize| board.get_cell(x + dx, y + dy) == CellState::Alive;
+        match self {
+            Glyphs::Braille => {
+                // Dots 1-3 and 7 are the left column, dots 4-6 and 8 the right one.
+                const DOTS: [(usize, usize, u32); 8] = [
+                    (0, 0, 0x01), (0, 1, 0x02), (0, 2, 0x04), (0, 3, 0x40),
+                    (1, 0, 0x08), (1, 1, 0x10), (1, 2, 0x20), (1, 3, 0x80),
+                ];
+                let bits = DOTS.iter().filter(|(dx, dy, _)| alive(*dx, *dy)).fold(0, |bits, (_, _, bit)| bits | bit);
+                char::from_u32(0x2800 + bits).unwrap_or(' ')
+            }
+            Glyphs::HalfBlock => match (alive(0, 0), alive(0, 1)) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            },
+        }
+    }
+}
+
+pub struct GameView {
+    controller: GameController,
+    glyphs: Glyphs,
+    offset_x: usize,
+    offset_y: usize,
+    stdout: Stdout,
+}
+
+impl GameView {
+    fn render(&mut self) -> std::io::Result<()> {
+        let (columns, rows) = size()?;
+        let (columns, rows) = (columns as usize, (rows as usize).saturating_sub(1));
+        let (cells_x, cells_y) = self.glyphs.cells_per_char();
+        let board = &self.controller.game_state.board;
+
+        for row in 0..rows {
+            let line: String = (0..columns)
+                .map(|column| self.glyphs.char_at(board, self.offset_x + column * cells_x, self.offset_y + row * cells_y))
+                .collect();
+            queue!(self.stdout, MoveTo(0, row as u16), Print(line))?;
+        }
+
+        let status = format!(
+            "Generation {} | Population {} | {} ms | {} | space: pause  n/m: step 1/10  r: reset  +/-: speed  arrows: pan  g: glyphs  q: quit",
+            self.controller.game_state.generation,
+            board.population(),
+            self.controller.update_interval().as_millis(),
+            if self.controller.is_paused() { "paused" } else { "running" },
+        );
+        let status: String = status.chars().take(columns).collect();
+        queue!(self.stdout, MoveTo(0, rows as u16), Clear(ClearType::CurrentLine), Print(status))?;
+        self.stdout.flush()
+    }
+
+    // Returns false when the user quits.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let (cells_x, cells_y) = self.glyphs.cells_per_char();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Char(' ') | KeyCode::Char('p') => self.toggle_pause(),
+            KeyCode::Char('n') | KeyCode::Char('.') => self.step(),
+            KeyCode::Char('m') => self.step_n(10),
+            KeyCode::Char('r') => self.reset(),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.controller.faster(),
+            KeyCode::Char('-') => self.controller.slower(),
+            KeyCode::Char('g') => {
+                self.glyphs = match self.glyphs {
+                    Glyphs::Braille => Glyphs::HalfBlock,
+                    Glyphs::HalfBlock => Glyphs::Braille,
+                };
+            }
+            KeyCode::Left => self.offset_x = self.offset_x.saturating_sub(PAN_STEP * cells_x),
+            KeyCode::Right => self.offset_x = (self.offset_x + PAN_STEP * cells_x).min(self.controller.game_state.board.grid_width.saturating_sub(1)),
+            KeyCode::Up => self.offset_y = self.offset_y.saturating_sub(PAN_STEP * cells_y),
+            KeyCode::Down => self.offset_y = (self.offset_y + PAN_STEP * cells_y).min(self.controller.game_state.board.grid_height.saturating_sub(1)),
+            _ => {}
+        }
+        true
+    }
+
+    fn run_loop(&mut self) -> std::io::Result<()> {
+        let mut last_update = Instant::now();
+        self.render()?;
+
+        loop {
+            let update_interval = self.controller.update_interval();
+            let timeout = if self.controller.is_paused() { update_interval } else { update_interval.saturating_sub(last_update.elapsed()) };
+            if poll(timeout)? {
+                match read()? {
+                    Event::Key(key) if key.kind != KeyEventKind::Release => {
+                        if !self.handle_key(key) {
+                            return Ok(());
+                        }
+                    }
+                    Event::Resize(_, _) => execute!(self.stdout, Clear(ClearType::All))?,
+                    _ => continue,
+                }
+                self.render()?;
+            }
+
+            if last_update.elapsed() >= self.controller.update_interval() {
+                last_update = Instant::now();
+                if self.controller.tick() {
+                    self.render()?;
+                }
+            }
+        }
+    }
+}
+
+impl GameViewTrait for GameView {
+    fn new(game_state: GameState, _cell_size: usize, update_interval_ms: usize) -> Self {
+        Self {
+            controller: GameController::new(game_state, Duration::from_millis(update_interval_ms as u64)),
+            glyphs: Glyphs::Braille,
+            offset_x: 0,
+            offset_y: 0,
+            stdout: stdout(),
+        }
+    }
+
+    fn init(&mut self) -> Result<(), String> {
+        enable_raw_mode().map_err(|error| format!("Cannot set up the terminal: {}", error))?;
+        // A panic would otherwise print its message on the alternate screen and leave the shell in raw mode.
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let _ = restore_terminal();
+            default_hook(info);
+        }));
+        let result = execute!(self.stdout, EnterAlternateScreen, Hide, Clear(ClearType::All))
+            .and_then(|_| self.run_loop());
+
+        // Puts the default hook back.
+        drop(panic::take_hook());
+        let restored = restore_terminal();
+        result.and(restored).map_err(|error| format!("Terminal error: {}", error))
+    }
+
+    fn controller(&mut self) -> &mut GameController {
+        &mut self.controller
+    }
+}
+
+fn restore_terminal() -> io::Result<()> {
+    execute!(stdout(), Show, LeaveAlternateScreen)?;
+    disable_raw_mode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(width: usize, height: usize, alive: &[(usize, usize)]) -> Board {
+        let mut board = Board::new(width, height);
+        for &(x, y) in alive {
+            board.set_cell(x, y, CellState::Alive);
+        }
+        board
+    }
+
+    #[test]
+    fn half_blocks_show_two_cells_stacked() {
+        let glyph = |alive: &[(usize, usize)]| Glyphs::HalfBlock.char_at(&board(1, 2, alive), 0, 0);
+        assert_eq!(glyph(&[]), ' ');
+        assert_eq!(glyph(&[(0, 0)]), '▀');
+        assert_eq!(glyph(&[(0, 1)]), '▄');
+        assert_eq!(glyph(&[(0, 0), (0, 1)]), '█');
+    }
+
+    #[test]
+    fn braille_dots_match_their_cells() {
+        let glyph = |alive: &[(usize, usize)]| Glyphs::Braille.char_at(&board(2, 4, alive), 0, 0);
+        assert_eq!(glyph(&[]), '\u{2800}');
+        assert_eq!(glyph(&[(0, 0)]), '⠁');
+        assert_eq!(glyph(&[(0, 3)]), '⡀');
+        assert_eq!(glyph(&[(1, 0)]), '⠈');
+        assert_eq!(glyph(&[(1, 3)]), '⢀');
+        let all: Vec<(usize, usize)> = (0..2).flat_map(|x| (0..4).map(move |y| (x, y))).collect();
+        assert_eq!(glyph(&all), '⣿');
+    }
+
+    #[test]
+    fn glyphs_start_at_the_given_cell_and_stop_at_the_edge() {
+        let board = board(3, 5, &[(2, 4), (1, 1)]);
+        assert_eq!(Glyphs::Braille.char_at(&board, 1, 1), '⢁');
+        assert_eq!(Glyphs::Braille.char_at(&board, 2, 2), '⠄');
+        assert_eq!(Glyphs::HalfBlock.char_at(&board, 2, 4), '▀');
+        assert_eq!(Glyphs::HalfBlock.char_at(&board, 3, 0), ' ');
+    }
+}
@@ -0,0 +1,130 @@
+use game_of_life::{parse_pattern, write_rle, Board, CellState, GameState, Placement, Rule, Topology};
+
+use crate::view::web::js::js_error;
+
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+
+// A board driven from JavaScript, without any canvas:
+//
+//     const game = new Game(64, 48);
+//     game.load_pattern(rle);
+//     game.step(10);
+//     console.log(game.generation, game.population, game.to_rle());
+#[wasm_bindgen]
+pub struct Game {
+    game_state: GameState,
+}
+
+#[wasm_bindgen]
+impl Game {
+    // Creates an empty board of `width` x `height` cells, with the B3/S23 rule on a bounded board. Sizes of 0, or
+    // larger than a parsed pattern can be, throw an `Error`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: usize, height: usize) -> Result<Game, JsValue> {
+        Board::check_size(width, height).map_err(|message| js_error(&message))?;
+        Ok(Game { game_state: GameState::new(width, height, 1) })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.game_state.board.grid_width
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.game_state.board.grid_height
+    }
+
+    // The generation number, as a JavaScript number rather than a BigInt.
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> f64 {
+        self.game_state.generation as f64
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn population(&self) -> usize {
+        self.game_state.board.population()
+    }
+
+    // Advances by one generation unless `generations` is given.
+    pub fn step(&mut self, generations: Option<u32>) {
+        self.game_state.advance(generations.unwrap_or(1) as u64);
+    }
+
+    pub fn get_cell(&self, x: usize, y: usize) -> Result<bool, JsValue> {
+        self.check_cell(x, y)?;
+        Ok(self.game_state.board.get_cell(x, y) == CellState::Alive)
+    }
+
+    pub fn set_cell(&mut self, x: usize, y: usize, alive: bool) -> Result<(), JsValue> {
+        self.check_cell(x, y)?;
+        self.game_state.board.set_cell(x, y, if alive { CellState::Alive } else { CellState::Dead });
+        Ok(())
+    }
+
+    // Kills every cell and restarts the generation count.
+    pub fn clear(&mut self) {
+        let board = &mut self.game_state.board;
+        board.fill_region(0, 0, board.grid_width, board.grid_height, CellState::Dead);
+        self.game_state.generation = 0;
+    }
+
+    // Places a plaintext or RLE pattern with its top-left corner at (x, y), centred if they are not given.
+    // The rule declared by an RLE pattern replaces the current rule.
+    pub fn load_pattern(&mut self, text: &str, x: Option<usize>, y: Option<usize>) -> Result<(), JsValue> {
+        let pattern = parse_pattern(text).map_err(|error| js_error(&error.to_string()))?;
+        let position = (x.is_some() || y.is_some()).then(|| (x.unwrap_or(0), y.unwrap_or(0)));
+        let placement = Placement { position, ..Placement::default() };
+        self.game_state.add_pattern(&pattern, &placement).map_err(|error| js_error(&error.to_string()))?;
+        if let Some(rule) = pattern.rule {
+            self.game_state.set_rule(rule);
+        }
+        Ok(())
+    }
+
+    // `rule` is a Life-like rule such as B36/S23.
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsValue> {
+        let rule: Rule = rule.parse().map_err(|message: String| js_error(&message))?;
+        self.game_state.set_rule(rule);
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rule(&self) -> String {
+        self.game_state.board.rule.to_string()
+    }
+
+    // `topology` is bounded or torus.
+    pub fn set_topology(&mut self, topology: &str) -> Result<(), JsValue> {
+        let topology: Topology = topology.parse().map_err(|message: String| js_error(&message))?;
+        self.game_state.set_topology(topology);
+        Ok(())
+    }
+
+    // Returns the live cells, cropped to their bounding box, as RLE with the current rule.
+    pub fn to_rle(&self) -> String {
+        write_rle(&self.game_state.board.to_pattern())
+    }
+
+    fn check_cell(&self, x: usize, y: usize) -> Result<(), JsValue> {
+        if x >= self.width() || y >= self.height() {
+            return Err(js_error(&format!("Cell ({}, {}) is outside the {}x{} board", x, y, self.width(), self.height())));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn boards_are_bounded() {
+        assert_eq!(Game::new(64, 48).map(|game| (game.width(), game.height())).ok(), Some((64, 48)));
+        assert!(Game::new(0, 48).is_err());
+        assert!(Game::new(100_001, 1).is_err());
+        assert!(Game::new(65536, 65536).is_err());
+    }
+}
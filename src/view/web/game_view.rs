@@ -1,148 +1,402 @@
-use crate::game::cell::Cell;
-use crate::game::cell_state::CellState;
-use crate::game::game_state::GameState;
+use game_of_life::{parse_pattern, Camera, Frame, GameState, Placement, SharedState};
+
+use crate::view::editor::Editor;
+use crate::view::game_controller::GameController;
+use crate::view::hud::Hud;
 use crate::view::game_view_trait::GameViewTrait;
 use crate::view::web::util::*;
 use crate::view::web::js::*;
+use crate::view::web::pointers::{Action, Pointers};
+use crate::view::web::worker::in_worker;
 
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::cell::RefCell;
-use wasm_bindgen::JsCast;
+use wasm_bindgen::{Clamped, JsCast};
 use std::time::{Duration};
-use rand::{Rng, thread_rng};
-use web_sys::HtmlCanvasElement;
+use web_sys::{Event, HtmlCanvasElement, ImageData, PointerEvent, WheelEvent};
 use wasm_bindgen::closure::Closure;
 use web_sys::{window, CanvasRenderingContext2d};
 
+thread_local! {
+    // The game of the page-level functions exported to JavaScript. Starting another one stops it.
+    static CURRENT_VIEW: RefCell<Option<RunningView>> = const { RefCell::new(None) };
+}
+
+// Runs `action` on the page-level game, returns None when no game has been started yet.
+pub fn with_current_view<T>(action: impl FnOnce(&mut GameView) -> T) -> Option<T> {
+    CURRENT_VIEW.with(|current| current.borrow().as_ref().map(|running| running.with_view(action)))
+}
+
 #[derive(Clone)]
 pub struct GameView {
-    game_state: GameState,
+    controller: GameController,
     cell_size: usize,
-    update_interval: Duration,
+    camera: Camera,
+    frame: Frame,
+    hud: Hud,
+    editor: Editor,
+    pointers: Pointers,
+    render_time_ms: f64,
     context: Option<CanvasRenderingContext2d>,
     last_update_time: f64,
-    canvas_ref: usize,
 }
 
 impl GameView {
-    const ALIVE_COLOR: &'static str = "black";
-    const DEAD_COLOR: &'static str = "white";
+    pub const ZOOM_STEP: f64 = 1.25;
 
-    fn render(&mut self) {
-        self.fill_background();
+    // Returns the zoom factor of a vertical wheel movement, None for horizontal-only scrolls, as on trackpads.
+    pub fn wheel_zoom(delta_y: f64) -> Option<f64> {
+        if delta_y < 0.0 {
+            Some(Self::ZOOM_STEP)
+        } else if delta_y > 0.0 {
+            Some(1.0 / Self::ZOOM_STEP)
+        } else {
+            None
+        }
+    }
 
-        let mut cells_to_draw = Vec::new();
-        for (x, row) in self.game_state.board.cells.iter().enumerate() {
-            for (y, cell) in row.iter().enumerate() {
-                if cell == &CellState::Alive {
-                    cells_to_draw.push(Cell::new(x, y, Self::ALIVE_COLOR));
-                }
+    pub fn camera(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    pub fn hud(&mut self) -> &mut Hud {
+        &mut self.hud
+    }
+
+    // Returns how long drawing the last frame took, in milliseconds.
+    pub fn render_time_ms(&self) -> f64 {
+        self.render_time_ms
+    }
+
+    // The current board with the speed and cell size it is shown at, to start the same game from a link.
+    pub fn shared_state(&self) -> SharedState {
+        let update_interval_ms = self.controller.update_interval().as_millis() as u64;
+        SharedState::from_board(&self.controller.game_state.board, update_interval_ms, self.cell_size)
+    }
+
+    // Draws the board and the HUD into the frame, in Rust memory.
+    pub fn draw(&mut self) -> &Frame {
+        let start_time = performance_now();
+        let board = &self.controller.game_state.board;
+        self.camera.follow(board);
+
+        let (width, height) = (self.camera.viewport.0 as usize, self.camera.viewport.1 as usize);
+        self.frame.resize(width, height);
+        let palette = self.controller.theme.palette();
+        self.frame.draw_board(board, &self.camera, &palette, self.controller.color_mode());
+        self.hud.observe(&self.controller.game_state, start_time);
+        self.hud.draw(&mut self.frame, &self.controller, &palette);
+
+        self.render_time_ms = performance_now() - start_time;
+        &self.frame
+    }
+
+    // Draws the frame and copies it to the canvas in one call.
+    pub fn render(&mut self) {
+        let Some(context) = self.context.clone() else {
+            return;
+        };
+        let frame = self.draw();
+        match ImageData::new_with_u8_clamped_array_and_sh(Clamped(&frame.pixels), frame.width as u32, frame.height as u32) {
+            Ok(image_data) => {
+                let _ = context.put_image_data(&image_data, 0.0, 0.0);
             }
+            Err(error) => console_log(&format!("Cannot draw the board: {:?}", error)),
         }
-        for cell in cells_to_draw {
-            self.draw_cell(cell);
+    }
+
+    // Advances the game when its interval has elapsed at `current_time`, returns whether the board changed.
+    pub fn advance(&mut self, current_time: f64) -> bool {
+        if current_time - self.last_update_time < self.controller.update_interval().as_millis() as f64 {
+            return false;
         }
+        self.last_update_time = current_time;
+        self.controller.tick()
     }
 
-    fn update(&mut self) {
-        let start_time = performance_now();
+    // Called at each animation frame with the current time.
+    fn animate(&mut self, current_time: f64) {
+        if self.advance(current_time) {
+            self.render();
+        }
+    }
+
+    // Fits the board to a canvas of `width` x `height` pixels, the live cells keep their place on the screen. Boards that
+    // keep their size, see `GameController::resize_board`, are only shown on the larger or smaller canvas.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let cells = |pixels: u32| (pixels as usize).div_ceil(self.cell_size);
+        let (dx, dy) = self.controller.resize_board(cells(width), cells(height));
+        self.camera.resize(width as f64, height as f64);
+        self.camera.center = (self.camera.center.0 + dx as f64, self.camera.center.1 + dy as f64);
+    }
 
-        self.game_state.update();
-        self.render();
+    // The pointer methods take positions in canvas pixels and return whether the view changed.
+    pub fn press_pointer(&mut self, id: i32, point: (f64, f64), button: i16, touch: bool) -> bool {
+        let actions = self.pointers.press(id, point, button, touch);
+        self.apply(actions)
+    }
 
-        let end_time = performance_now();
-        if self.update_interval.as_millis() > 1000 {
-            console_log(&format!("Rendering, took {} ms", end_time - start_time));
+    pub fn move_pointer(&mut self, id: i32, point: (f64, f64)) -> bool {
+        let actions = self.pointers.move_to(id, point);
+        self.apply(actions)
+    }
+
+    pub fn release_pointer(&mut self, id: i32) -> bool {
+        let actions = self.pointers.release(id);
+        self.apply(actions)
+    }
+
+    fn apply(&mut self, actions: Vec<Action>) -> bool {
+        for action in &actions {
+            match *action {
+                Action::MoveCursor((x, y)) => {
+                    let cell = self.camera.cell_at(&self.controller.game_state.board, x, y);
+                    self.editor.move_cursor(&mut self.controller, cell);
+                }
+                Action::Press => self.editor.press_primary(&mut self.controller),
+                Action::Release => self.editor.release_primary(),
+                Action::Pan(dx, dy) => self.camera.pan(dx, dy),
+                Action::Zoom(factor, (x, y)) => self.camera.zoom_at(factor, x, y),
+            }
         }
+        !actions.is_empty()
+    }
+
+    // Replaces the board with a plaintext or RLE pattern, centred, which `reset()` then goes back to.
+    pub fn load_pattern(&mut self, text: &str) -> Result<(), String> {
+        let pattern = parse_pattern(text).map_err(|error| error.to_string())?;
+        let board = &self.controller.game_state.board;
+        let mut game_state = GameState::new(board.grid_width, board.grid_height, 1);
+        game_state.set_rule(pattern.rule.unwrap_or(board.rule));
+        game_state.set_topology(board.topology);
+        game_state.add_pattern(&pattern, &Placement::default()).map_err(|error| error.to_string())?;
+        self.controller.restart(game_state);
+        self.controller.clipboard = Some(pattern);
+        Ok(())
     }
 
-    fn fill_background(&mut self) {
-        let context = self.context.as_mut().unwrap();
-        context.begin_path();
-        context.rect(0.0, 0.0, (self.game_state.board.grid_width * self.cell_size) as f64, (self.game_state.board.grid_width * self.cell_size) as f64);
-        context.set_fill_style(&Self::DEAD_COLOR.into());
-        context.fill();
-        context.stroke();
+    // Fills the board randomly, each cell being alive with the given probability.
+    pub fn randomize(&mut self, probability: f64) {
+        let mut game_state = self.controller.game_state.clone();
+        game_state.generation = 0;
+        game_state.init(probability.clamp(0.0, 1.0));
+        self.controller.restart(game_state);
     }
 
-    fn draw_cell(&mut self, cell: Cell) {
-        let context = self.context.as_mut().unwrap();
-        context.begin_path();
-        context.rect((cell.get_x() * self.cell_size) as f64, (cell.get_y() * self.cell_size) as f64, self.cell_size as f64, self.cell_size as f64);
-        context.set_fill_style(&cell.get_color().into());
-        context.fill();
-        context.stroke();
+    // Sizes the canvas to the board and gets its drawing context.
+    fn attach(&mut self, canvas: &HtmlCanvasElement) -> Result<(), String> {
+        let board = &self.controller.game_state.board;
+        canvas.set_width((board.grid_width * self.cell_size) as u32);
+        canvas.set_height((board.grid_height * self.cell_size) as u32);
+        self.context = Some(context_2d(canvas)?);
+        Ok(())
     }
+}
 
-    fn render_loop_aux(self_rc: Rc<RefCell<Self>>) {
-        let performance = window().unwrap().performance().unwrap();
-        let current_time = performance.now();
-        let mut game_view = self_rc.borrow_mut();
+// A canvas event listener, with the name of the event it is registered for.
+pub type Listener = (&'static str, Closure<dyn FnMut(Event)>);
 
-        let canvas = window().unwrap().document().unwrap().get_element_by_id("canvas").unwrap();
-        let exists_canvas_ref = canvas.get_attribute("data-game-canvas-ref").unwrap() == game_view.canvas_ref.to_string();
+// The animation frame loop of a running view: the pending request and the callback it will call.
+#[derive(Default)]
+struct Animation {
+    request_id: Option<i32>,
+    callback: Option<Closure<dyn FnMut(f64)>>,
+}
 
-        if exists_canvas_ref {
-            if current_time - game_view.last_update_time >= game_view.update_interval.as_millis() as f64 {
-                game_view.update();
-                game_view.last_update_time = current_time;
-            }
+// A view drawing on a canvas, animated until stopped or dropped.
+pub struct RunningView {
+    view: Rc<RefCell<GameView>>,
+    canvas: HtmlCanvasElement,
+    listeners: Vec<Listener>,
+    animation: Rc<RefCell<Animation>>,
+    size_observer: Option<SizeObserver>,
+}
+
+impl RunningView {
+    // The callbacks only hold weak references to the view, so dropping the running view frees it.
+    pub fn start(mut view: GameView, canvas: HtmlCanvasElement) -> Result<RunningView, String> {
+        view.attach(&canvas)?;
+        view.render();
+        // Touches draw on the board instead of scrolling the page.
+        let _ = canvas.style().set_property("touch-action", "none");
+        let mut running = RunningView {
+            view: Rc::new(RefCell::new(view)),
+            canvas,
+            listeners: Vec::new(),
+            animation: Rc::new(RefCell::new(Animation::default())),
+            size_observer: None,
+        };
+        running.add_pointer_listeners();
+        running.request_animation_frames();
+        Ok(running)
+    }
+
+    pub fn with_view<T>(&self, action: impl FnOnce(&mut GameView) -> T) -> T {
+        action(&mut self.view.borrow_mut())
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.animation.borrow().request_id.is_some()
+    }
+
+    // Cancels the animation frame loop and removes the listeners from the canvas. The board stays drawn.
+    pub fn stop(&mut self) {
+        let mut animation = self.animation.borrow_mut();
+        if let Some(request_id) = animation.request_id.take() {
+            let _ = window().unwrap().cancel_animation_frame(request_id);
         }
+        animation.callback = None;
+        self.size_observer = None;
+        for (event, listener) in self.listeners.drain(..) {
+            let _ = self.canvas.remove_event_listener_with_callback(event, listener.as_ref().unchecked_ref());
+        }
+    }
+
+    // Resizes the canvas and the board with the parent element of the canvas, whose size should not depend on the
+    // canvas, for instance a block with a height set in CSS.
+    pub fn fit_container(&mut self) -> Result<(), String> {
+        let container = self.canvas.parent_element().ok_or("The canvas has no parent element")?;
+        let (view, canvas) = (Rc::downgrade(&self.view), self.canvas.clone());
+        self.size_observer = Some(SizeObserver::new(&container, move |width, height| {
+            let Some(view) = view.upgrade() else {
+                return;
+            };
+            if width > 0 && height > 0 && (canvas.width(), canvas.height()) != (width, height) {
+                canvas.set_width(width);
+                canvas.set_height(height);
+                let mut view = view.borrow_mut();
+                view.resize(width, height);
+                view.render();
+            }
+        })?);
+        Ok(())
+    }
 
-        drop(game_view);
+    fn add_listener(&mut self, event: &'static str, mut action: impl FnMut(&mut GameView, Event) + 'static) {
+        let view = Rc::downgrade(&self.view);
+        let listener = Closure::wrap(Box::new(move |event: Event| {
+            if let Some(view) = view.upgrade() {
+                action(&mut view.borrow_mut(), event);
+            }
+        }) as Box<dyn FnMut(Event)>);
+        let _ = self.canvas.add_event_listener_with_callback(event, listener.as_ref().unchecked_ref());
+        self.listeners.push((event, listener));
+    }
 
-        let f = Rc::new(RefCell::new(None));
-        let g = f.clone();
+    // Zooms with the wheel around the pointer. The pointers draw and pan, and fingers also pinch, see `Pointers`.
+    fn add_pointer_listeners(&mut self) {
+        let canvas = self.canvas.clone();
+        self.add_listener("wheel", move |view, event| {
+            let event: WheelEvent = event.unchecked_into();
+            let Some(factor) = GameView::wheel_zoom(event.delta_y()) else {
+                return;
+            };
+            event.prevent_default();
+            let (x, y) = canvas_point(&canvas, event.client_x(), event.client_y());
+            view.camera.zoom_at(factor, x, y);
+            view.render();
+        });
+        let canvas = self.canvas.clone();
+        self.add_listener("pointerdown", move |view, event| {
+            let event: PointerEvent = event.unchecked_into();
+            event.prevent_default();
+            // Keeps receiving the moves of a stroke that leaves the canvas.
+            let _ = canvas.set_pointer_capture(event.pointer_id());
+            let point = canvas_point(&canvas, event.client_x(), event.client_y());
+            if view.press_pointer(event.pointer_id(), point, event.button(), event.pointer_type() == "touch") {
+                view.render();
+            }
+        });
+        let canvas = self.canvas.clone();
+        self.add_listener("pointermove", move |view, event| {
+            let event: PointerEvent = event.unchecked_into();
+            if view.move_pointer(event.pointer_id(), canvas_point(&canvas, event.client_x(), event.client_y())) {
+                view.render();
+            }
+        });
+        for name in ["pointerup", "pointercancel"] {
+            self.add_listener(name, |view, event| {
+                let event: PointerEvent = event.unchecked_into();
+                if view.release_pointer(event.pointer_id()) {
+                    view.render();
+                }
+            });
+        }
+        // The secondary button pans rather than opening the menu.
+        self.add_listener("contextmenu", |_, event| event.prevent_default());
+    }
 
-        *g.borrow_mut() = Some(Closure::wrap(Box::new(move || {
-            GameView::render_loop_aux(self_rc.clone());
-            let _ = f.borrow_mut().take();
-        }) as Box<dyn FnMut()>));
+    fn request_animation_frames(&mut self) {
+        let view = Rc::downgrade(&self.view);
+        let animation: Weak<RefCell<Animation>> = Rc::downgrade(&self.animation);
+        let callback = Closure::wrap(Box::new(move |current_time: f64| {
+            let (Some(view), Some(animation)) = (view.upgrade(), animation.upgrade()) else {
+                return;
+            };
+            view.borrow_mut().animate(current_time);
+            let mut animation = animation.borrow_mut();
+            // Stopping clears the request, which ends the loop.
+            if animation.request_id.is_some() {
+                animation.request_id = request_animation_frame(animation.callback.as_ref().unwrap());
+            }
+        }) as Box<dyn FnMut(f64)>);
 
-        window().unwrap().request_animation_frame(g.borrow().as_ref().unwrap().as_ref().unchecked_ref()).unwrap();
+        let mut animation = self.animation.borrow_mut();
+        animation.request_id = request_animation_frame(&callback);
+        animation.callback = Some(callback);
     }
+}
 
-    fn render_loop(&mut self) {
-        let self_rc = Rc::new(RefCell::new(self.clone()));
-        GameView::render_loop_aux(self_rc);
+impl Drop for RunningView {
+    fn drop(&mut self) {
+        self.stop();
     }
 }
 
+fn request_animation_frame(callback: &Closure<dyn FnMut(f64)>) -> Option<i32> {
+    window().unwrap().request_animation_frame(callback.as_ref().unchecked_ref()).ok()
+}
+
 impl GameViewTrait for GameView {
     fn new(game_state: GameState, cell_size: usize, update_interval_ms: usize) -> Self {
         console_log("New GameView");
 
-        let mut rng = thread_rng();
+        let (board_width, board_height) = (game_state.board.grid_width, game_state.board.grid_height);
 
         Self {
-            game_state,
+            controller: GameController::new(game_state, Duration::from_millis(update_interval_ms as u64)),
             cell_size,
-            update_interval: Duration::from_millis(update_interval_ms as u64),
+            camera: Camera::new((board_width * cell_size) as f64, (board_height * cell_size) as f64, cell_size as f64),
+            frame: Frame::new(board_width * cell_size, board_height * cell_size),
+            hud: Hud::default(),
+            editor: Editor::default(),
+            pointers: Pointers::default(),
+            render_time_ms: 0.0,
             last_update_time: 0.0,
             context: None,
-            canvas_ref: rng.gen(),
         }
     }
 
+    // Starts the page-level game on the element with the id "canvas", stopping the previous one.
     fn init(&mut self) -> Result<(), String> {
+        // The module is also loaded by the worker script, where the page drives the game with messages instead.
+        if in_worker() {
+            return Ok(());
+        }
         console_log("Initializing");
-        let window = window().expect("no global `window` exists");
-        let document = window.document().expect("should have a document on window");
-        let canvas = document.get_element_by_id("canvas").unwrap();
-        let canvas: HtmlCanvasElement = canvas.dyn_into::<HtmlCanvasElement>().unwrap();
-
-        canvas.set_width((self.game_state.board.grid_width * self.cell_size) as u32);
-        canvas.set_height((self.game_state.board.grid_height * self.cell_size) as u32);
-        let _ = canvas.set_attribute("data-game-canvas-ref", &self.canvas_ref.to_string());
-
-        self.context = Some(
-            canvas.get_context("2d")
-                .unwrap()
-                .unwrap()
-                .dyn_into::<CanvasRenderingContext2d>()
-                .unwrap()
-        );
-        self.render_loop();
+        let document = window().and_then(|window| window.document()).ok_or("No document to draw in")?;
+        let canvas = document.get_element_by_id("canvas")
+            .and_then(|canvas| canvas.dyn_into::<HtmlCanvasElement>().ok())
+            .ok_or("No canvas with the id 'canvas' on the page")?;
+
+        CURRENT_VIEW.with(|current| current.borrow_mut().take());
+        let mut running = RunningView::start(self.clone(), canvas)?;
+        running.fit_container()?;
+        CURRENT_VIEW.with(|current| *current.borrow_mut() = Some(running));
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn controller(&mut self) -> &mut GameController {
+        &mut self.controller
+    }
+}
@@ -1,8 +1,18 @@
+use game_of_life::{is_image_path, parse_image_pattern, parse_pattern, ColorMode, ImageImportOptions, Pattern, Theme, MAX_PATTERN_SIZE};
+
+use crate::cli::{self, Options};
+use crate::view::web::game_view::with_current_view;
+use crate::view::web::worker::Request;
+
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::js_sys::Promise;
 use wasm_bindgen_futures::{js_sys, JsFuture};
 
+const PAGE_CELL_SIZE: usize = 10;
+// Dead cells left around a pattern started on the page, in cells, so that it can grow before reaching the edges.
+const PATTERN_MARGIN: usize = 8;
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console, js_name = log)]
@@ -13,19 +23,148 @@ extern "C" {
 }
 
 #[wasm_bindgen]
-pub async fn load_pattern_in_rust(pattern_filename: &str) {
+pub async fn load_pattern_in_rust(pattern_filename: &str) -> Result<(), JsValue> {
     console_log(("Loading pattern ".to_owned() + pattern_filename).as_str());
-    match make_fetch_call(pattern_filename).await {
-        Ok(text) => {
-            console_log(&text);
-            let pattern: Vec<String> = text.lines().map(|line| line.to_string()).collect();
-            let cell_size: u8 = 10;
-            crate::initialize_game(Some(pattern), cell_size);
-        }
-        Err(err) => {
-            console_log(err.as_string().unwrap().as_str());
-        }
-    }
+    let text = make_fetch_call(pattern_filename).await?;
+    load_pattern_text(&text)
+}
+
+// Starts the page-level game with a plaintext or RLE pattern, pasted by the user for instance.
+#[wasm_bindgen]
+pub fn load_pattern_text(text: &str) -> Result<(), JsValue> {
+    let pattern = parse_pattern(text).map_err(|error| js_error(&error.to_string()))?;
+    start_pattern(pattern)
+}
+
+// Starts the page-level game with the content of a file dropped by the user. PNG, PPM and PGM images, recognised by
+// `file_name`, are imported with their grid detected, other files are read as plaintext or RLE.
+#[wasm_bindgen]
+pub fn load_pattern_file(file_name: &str, bytes: &[u8]) -> Result<(), JsValue> {
+    let pattern = if is_image_path(file_name) {
+        parse_image_pattern(bytes, &ImageImportOptions { cell_size: None, ..ImageImportOptions::default() })
+    } else {
+        let text = std::str::from_utf8(bytes).map_err(|_| js_error(&format!("'{}' is not a text file", file_name)))?;
+        parse_pattern(text)
+    };
+    start_pattern(pattern.map_err(|error| js_error(&error.to_string()))?)
+}
+
+fn start_pattern(pattern: Pattern) -> Result<(), JsValue> {
+    let options = pattern_options(&pattern).map_err(|message| js_error(&message))?;
+    crate::initialize_game(Some(pattern), &options).map_err(|message| js_error(&message))
+}
+
+// Returns the options of the page-level game started with a pattern. The board is at least the default size of the
+// page, with 10 pixel cells, and grows to hold the pattern and a margin around it, with smaller cells for large patterns.
+fn pattern_options(pattern: &Pattern) -> Result<Options, String> {
+    let defaults = Options::default();
+    let cells = |pattern_size: usize| (pattern_size + 2 * PATTERN_MARGIN).min(MAX_PATTERN_SIZE);
+    let (width, height) = (cells(pattern.width), cells(pattern.height));
+    let cell_size = (defaults.width / width).min(defaults.height / height).clamp(1, PAGE_CELL_SIZE);
+    let options = Options {
+        width: (width * cell_size).max(defaults.width),
+        height: (height * cell_size).max(defaults.height),
+        cell_size,
+        ..defaults
+    };
+    cli::validate(&options)?;
+    Ok(options)
+}
+
+#[wasm_bindgen]
+pub fn play() -> Result<(), JsValue> {
+    control(Request::Play)
+}
+
+#[wasm_bindgen]
+pub fn pause() -> Result<(), JsValue> {
+    control(Request::Pause)
+}
+
+#[wasm_bindgen]
+pub fn toggle_pause() -> Result<(), JsValue> {
+    control(Request::TogglePause)
+}
+
+// Advances by one generation unless `generations` is given.
+#[wasm_bindgen]
+pub fn step(generations: Option<u32>) -> Result<(), JsValue> {
+    control(Request::Step(generations.unwrap_or(1)))
+}
+
+#[wasm_bindgen]
+pub fn set_speed(update_interval_ms: u32) -> Result<(), JsValue> {
+    control(Request::SetSpeed(update_interval_ms))
+}
+
+#[wasm_bindgen]
+pub fn reset() -> Result<(), JsValue> {
+    control(Request::Reset)
+}
+
+#[wasm_bindgen]
+pub fn zoom_in() -> Result<(), JsValue> {
+    control(Request::ZoomIn)
+}
+
+#[wasm_bindgen]
+pub fn zoom_out() -> Result<(), JsValue> {
+    control(Request::ZoomOut)
+}
+
+// Keeps the whole pattern in view, or stops doing so if it already did.
+#[wasm_bindgen]
+pub fn fit_pattern() -> Result<(), JsValue> {
+    control(Request::FitPattern)
+}
+
+// Keeps the view centred on the pattern, or stops doing so if it already did.
+#[wasm_bindgen]
+pub fn follow_centroid() -> Result<(), JsValue> {
+    control(Request::FollowCentroid)
+}
+
+// `theme` is light, dark or amber.
+#[wasm_bindgen]
+pub fn set_theme(theme: &str) -> Result<(), JsValue> {
+    let theme: Theme = theme.parse().map_err(|message: String| js_error(&message))?;
+    control(Request::SetTheme(theme))
+}
+
+// `color_mode` is plain, age or trail.
+#[wasm_bindgen]
+pub fn set_color_mode(color_mode: &str) -> Result<(), JsValue> {
+    let color_mode: ColorMode = color_mode.parse().map_err(|message: String| js_error(&message))?;
+    control(Request::SetColorMode(color_mode))
+}
+
+#[wasm_bindgen]
+pub fn toggle_hud() -> Result<(), JsValue> {
+    control(Request::ToggleHud)
+}
+
+// Returns how long drawing the last frame took, in milliseconds.
+#[wasm_bindgen]
+pub fn render_time() -> Result<f64, JsValue> {
+    with_current_view(|view| view.render_time_ms()).ok_or_else(no_game_error)
+}
+
+fn control(request: Request) -> Result<(), JsValue> {
+    with_current_view(|view| {
+        request.apply(view)?;
+        view.render();
+        Ok(())
+    })
+    .ok_or_else(no_game_error)?
+    .map_err(|message: String| js_error(&message))
+}
+
+pub fn no_game_error() -> JsValue {
+    js_error("No game is running, load a pattern first")
+}
+
+pub fn js_error(message: &str) -> JsValue {
+    js_sys::Error::new(message).into()
 }
 
 pub async fn make_fetch_call(url: &str) -> Result<String, JsValue> {
@@ -45,6 +184,10 @@ pub async fn make_fetch_call(url: &str) -> Result<String, JsValue> {
             JsValue::from_str("Response conversion error")
         })?;
 
+    if !response.ok() {
+        return Err(js_error(&format!("Cannot fetch '{}': HTTP status {}", url, response.status())));
+    }
+
     let text = JsFuture::from(response.text()?)
         .await
         .map(|text| text.as_string().unwrap_or_else(|| "".to_string()))
@@ -55,3 +198,24 @@ pub async fn make_fetch_call(url: &str) -> Result<String, JsValue> {
 
     Ok(text)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn boards_grow_to_hold_their_pattern() {
+        let glider = parse_pattern("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+        let options = pattern_options(&glider).unwrap();
+        assert_eq!((options.width, options.height, options.cell_size), (600, 500, 10));
+
+        let wide = parse_pattern("x = 300, y = 20\n300o!").unwrap();
+        let options = pattern_options(&wide).unwrap();
+        assert_eq!((options.width, options.height, options.cell_size), (600, 500, 1));
+
+        let huge = parse_pattern("x = 2000, y = 1\n2000o!").unwrap();
+        let options = pattern_options(&huge).unwrap();
+        assert_eq!((options.width, options.height, options.cell_size), (2016, 500, 1));
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(target_arch = "wasm32")]
+pub mod api;
+
 #[cfg(target_arch = "wasm32")]
 pub mod game_view;
 
@@ -6,3 +9,18 @@ pub mod js;
 
 #[cfg(target_arch = "wasm32")]
 pub mod util;
+
+#[cfg(target_arch = "wasm32")]
+pub mod pointers;
+
+#[cfg(target_arch = "wasm32")]
+pub mod share;
+
+#[cfg(target_arch = "wasm32")]
+pub mod simulation;
+
+#[cfg(target_arch = "wasm32")]
+pub mod worker;
+
+#[cfg(target_arch = "wasm32")]
+pub mod worker_simulation;
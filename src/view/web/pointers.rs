@@ -0,0 +1,146 @@
+// What the pointers held down on the canvas are doing.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+enum Mode {
+    #[default]
+    Idle,
+    // A touch that draws once it moves or is lifted, unless a second finger turns it into a pinch first.
+    PendingDraw,
+    Draw,
+    Pan,
+    Pinch,
+}
+
+// What the view does in response to the pointers. Points are in canvas pixels.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    MoveCursor((f64, f64)),
+    Press,
+    Release,
+    Pan(f64, f64),
+    // Zooms by `factor` around the point.
+    Zoom(f64, (f64, f64)),
+}
+
+// Turns the pointers held down on the canvas into strokes, pans and pinches. The primary button draws, the others pan,
+// a single finger draws and two fingers pan and pinch.
+#[derive(Clone, Default)]
+pub struct Pointers {
+    // The id and last position of each pointer held down, in the order they were pressed.
+    down: Vec<(i32, (f64, f64))>,
+    mode: Mode,
+}
+
+impl Pointers {
+    // `button` is 0 for the primary button, `touch` is true for fingers.
+    pub fn press(&mut self, id: i32, point: (f64, f64), button: i16, touch: bool) -> Vec<Action> {
+        self.down.retain(|(down_id, _)| *down_id != id);
+        self.down.push((id, point));
+        match (self.down.len(), self.mode) {
+            (1, _) if button != 0 => self.switch(Mode::Pan, vec![]),
+            (1, _) if touch => self.switch(Mode::PendingDraw, vec![]),
+            (1, _) => self.switch(Mode::Draw, vec![Action::MoveCursor(point), Action::Press]),
+            (2, Mode::Draw) => self.switch(Mode::Pinch, vec![Action::Release]),
+            (2, _) => self.switch(Mode::Pinch, vec![]),
+            _ => vec![],
+        }
+    }
+
+    pub fn move_to(&mut self, id: i32, point: (f64, f64)) -> Vec<Action> {
+        let Some(index) = self.down.iter().position(|(down_id, _)| *down_id == id) else {
+            return vec![];
+        };
+        let previous = self.down[index].1;
+        let pinch_before = self.pinch();
+        self.down[index].1 = point;
+        match self.mode {
+            Mode::PendingDraw => self.switch(Mode::Draw, vec![Action::MoveCursor(previous), Action::Press, Action::MoveCursor(point)]),
+            Mode::Draw => vec![Action::MoveCursor(point)],
+            Mode::Pan => vec![Action::Pan(point.0 - previous.0, point.1 - previous.1)],
+            Mode::Pinch => match (pinch_before, self.pinch()) {
+                (Some((center_before, distance_before)), Some((center, distance))) if index < 2 && distance_before > 0.0 => vec![
+                    Action::Pan(center.0 - center_before.0, center.1 - center_before.1),
+                    Action::Zoom(distance / distance_before, center),
+                ],
+                _ => vec![],
+            },
+            Mode::Idle => vec![],
+        }
+    }
+
+    // Also called when the browser cancels the pointer.
+    pub fn release(&mut self, id: i32) -> Vec<Action> {
+        let Some(index) = self.down.iter().position(|(down_id, _)| *down_id == id) else {
+            return vec![];
+        };
+        let (_, point) = self.down.remove(index);
+        match (self.mode, self.down.len()) {
+            // A tap toggles the cell under the finger.
+            (Mode::PendingDraw, _) => self.switch(Mode::Idle, vec![Action::MoveCursor(point), Action::Press, Action::Release]),
+            (Mode::Draw, _) => self.switch(Mode::Idle, vec![Action::Release]),
+            // The finger left after a pinch pans.
+            (Mode::Pinch, 1) => self.switch(Mode::Pan, vec![]),
+            (_, 0) => self.switch(Mode::Idle, vec![]),
+            _ => vec![],
+        }
+    }
+
+    fn switch(&mut self, mode: Mode, actions: Vec<Action>) -> Vec<Action> {
+        self.mode = mode;
+        actions
+    }
+
+    // The centre of the first two pointers and the distance between them.
+    fn pinch(&self) -> Option<((f64, f64), f64)> {
+        let [(_, a), (_, b), ..] = self.down.as_slice() else {
+            return None;
+        };
+        Some((((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0), (a.0 - b.0).hypot(a.1 - b.1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn the_primary_button_draws_at_once() {
+        let mut pointers = Pointers::default();
+        assert_eq!(pointers.press(1, (5.0, 5.0), 0, false), vec![Action::MoveCursor((5.0, 5.0)), Action::Press]);
+        assert_eq!(pointers.move_to(1, (9.0, 5.0)), vec![Action::MoveCursor((9.0, 5.0))]);
+        assert_eq!(pointers.release(1), vec![Action::Release]);
+        assert_eq!(pointers.move_to(1, (12.0, 5.0)), vec![]);
+    }
+
+    #[wasm_bindgen_test]
+    fn other_buttons_pan() {
+        let mut pointers = Pointers::default();
+        assert_eq!(pointers.press(1, (5.0, 5.0), 1, false), vec![]);
+        assert_eq!(pointers.move_to(1, (8.0, 1.0)), vec![Action::Pan(3.0, -4.0)]);
+        assert_eq!(pointers.release(1), vec![]);
+    }
+
+    #[wasm_bindgen_test]
+    fn a_tap_toggles_a_cell_and_a_drag_paints() {
+        let mut pointers = Pointers::default();
+        assert_eq!(pointers.press(1, (5.0, 5.0), 0, true), vec![]);
+        assert_eq!(pointers.release(1), vec![Action::MoveCursor((5.0, 5.0)), Action::Press, Action::Release]);
+
+        pointers.press(2, (5.0, 5.0), 0, true);
+        assert_eq!(pointers.move_to(2, (7.0, 5.0)), vec![Action::MoveCursor((5.0, 5.0)), Action::Press, Action::MoveCursor((7.0, 5.0))]);
+        assert_eq!(pointers.release(2), vec![Action::Release]);
+    }
+
+    #[wasm_bindgen_test]
+    fn two_fingers_pan_and_pinch_without_drawing() {
+        let mut pointers = Pointers::default();
+        pointers.press(1, (10.0, 10.0), 0, true);
+        assert_eq!(pointers.press(2, (20.0, 10.0), 0, true), vec![]);
+        assert_eq!(pointers.move_to(2, (30.0, 10.0)), vec![Action::Pan(5.0, 0.0), Action::Zoom(2.0, (20.0, 10.0))]);
+
+        assert_eq!(pointers.release(1), vec![]);
+        assert_eq!(pointers.move_to(2, (32.0, 11.0)), vec![Action::Pan(2.0, 1.0)]);
+        assert_eq!(pointers.release(2), vec![]);
+        assert_eq!(pointers.press(3, (1.0, 1.0), 0, false), vec![Action::MoveCursor((1.0, 1.0)), Action::Press]);
+    }
+}
@@ -0,0 +1,74 @@
+use game_of_life::{Pattern, SharedState};
+
+use crate::cli::{self, Options};
+use crate::view::web::game_view::with_current_view;
+use crate::view::web::js::{console_log, no_game_error};
+
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+use web_sys::window;
+
+// Returns the URL fragment, without the `#`, that starts the page-level game from its current board.
+#[wasm_bindgen]
+pub fn share_link() -> Result<String, JsValue> {
+    with_current_view(|view| view.shared_state().to_fragment()).ok_or_else(no_game_error)
+}
+
+// The game encoded in the fragment of the page URL, as the pattern and the options to start it with. A fragment that
+// is not a valid shared link is rejected, with the reason logged, and the page starts as if there were none.
+pub fn shared_start(options: &Options) -> Option<(Pattern, Options)> {
+    let hash = window()?.location().hash().ok()?;
+    let fragment = hash.strip_prefix('#').filter(|fragment| !fragment.is_empty())?;
+    match shared_options(fragment, options) {
+        Ok(start) => Some(start),
+        Err(message) => {
+            console_log(&format!("Ignoring the link: {}", message));
+            None
+        }
+    }
+}
+
+fn shared_options(fragment: &str, options: &Options) -> Result<(Pattern, Options), String> {
+    let shared = SharedState::from_fragment(fragment)?;
+    let pixels = |cells: usize| cells.checked_mul(shared.cell_size).ok_or_else(|| "The board of the link is too large".to_string());
+    let options = Options {
+        width: pixels(shared.board_size.0)?,
+        height: pixels(shared.board_size.1)?,
+        cell_size: shared.cell_size,
+        update_interval_ms: usize::try_from(shared.update_interval_ms).map_err(|_| "The interval of the link is too long".to_string())?,
+        rule: Some(shared.rule),
+        topology: shared.topology,
+        placement: shared.placement(),
+        keep_board_size: true,
+        ..options.clone()
+    };
+    cli::validate(&options)?;
+    Ok((shared.pattern, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_of_life::{parse_pattern, Board, Placement};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn glider_link(update_interval_ms: u64) -> String {
+        let mut board = Board::new(30, 20);
+        let glider = parse_pattern("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+        board.add_pattern(&glider, &Placement { position: Some((12, 5)), ..Placement::default() }).unwrap();
+        SharedState::from_board(&board, update_interval_ms, 6).to_fragment()
+    }
+
+    #[wasm_bindgen_test]
+    fn links_start_with_their_options() {
+        let (pattern, options) = shared_options(&glider_link(80), &Options::default()).unwrap();
+        assert_eq!((pattern.width, pattern.height), (3, 3));
+        assert_eq!((options.width, options.height, options.cell_size, options.update_interval_ms), (180, 120, 6, 80));
+    }
+
+    #[wasm_bindgen_test]
+    fn links_with_intervals_the_game_cannot_run_are_rejected() {
+        assert!(shared_options(&glider_link(1), &Options::default()).is_err());
+        assert!(shared_options(&glider_link(60_000), &Options::default()).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use game_of_life::{ColorMode, GameState, Theme};
+
+use crate::cli::{self, Options};
+use crate::view::game_view_trait::GameViewTrait;
+use crate::view::web::game_view::{GameView, RunningView};
+use crate::view::web::js::js_error;
+use crate::view::web::worker::Request;
+
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+use web_sys::HtmlCanvasElement;
+
+const DEFAULT_CELL_SIZE: usize = 10;
+
+// A game animated on a canvas chosen by the page, so that a page can show several of them:
+//
+//     const simulation = new Simulation(document.querySelector('#glider'), 40, 30);
+//     simulation.load_pattern(rle);
+//     ...
+//     simulation.stop();
+//
+// It has the controls of the page-level functions of `js.rs`, but does not look the canvas up by id. `stop()` ends the animation and
+// removes the listeners from the canvas, and so does `free()`, which also releases the memory of the game.
+#[wasm_bindgen]
+pub struct Simulation {
+    running: RunningView,
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    // Starts an empty board of `width` x `height` cells, each `cell_size` pixels wide (10 by default), updated every
+    // `update_interval_ms` milliseconds (the default of the page otherwise).
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas: HtmlCanvasElement, width: usize, height: usize, cell_size: Option<usize>,
+               update_interval_ms: Option<u32>) -> Result<Simulation, JsValue> {
+        let options = simulation_options(width, height, cell_size, update_interval_ms).map_err(|message| js_error(&message))?;
+        let game_state = GameState::new(options.width, options.height, options.cell_size);
+        let mut view = GameView::new(game_state, options.cell_size, options.update_interval_ms);
+        let controller = view.controller();
+        controller.theme = options.theme;
+        controller.set_color_mode(options.color_mode);
+        let running = RunningView::start(view, canvas).map_err(|message| js_error(&message))?;
+        Ok(Simulation { running })
+    }
+
+    // Replaces the board with a plaintext or RLE pattern, centred, which `reset()` then goes back to.
+    pub fn load_pattern(&self, text: &str) -> Result<(), JsValue> {
+        self.control(Request::LoadPattern(text.to_string()))
+    }
+
+    // Fills the board randomly, each cell being alive with the given probability.
+    pub fn randomize(&self, probability: f64) -> Result<(), JsValue> {
+        self.control(Request::Randomize(probability))
+    }
+
+    pub fn play(&self) -> Result<(), JsValue> {
+        self.control(Request::Play)
+    }
+
+    pub fn pause(&self) -> Result<(), JsValue> {
+        self.control(Request::Pause)
+    }
+
+    pub fn toggle_pause(&self) -> Result<(), JsValue> {
+        self.control(Request::TogglePause)
+    }
+
+    // Advances by one generation unless `generations` is given.
+    pub fn step(&self, generations: Option<u32>) -> Result<(), JsValue> {
+        self.control(Request::Step(generations.unwrap_or(1)))
+    }
+
+    pub fn set_speed(&self, update_interval_ms: u32) -> Result<(), JsValue> {
+        self.control(Request::SetSpeed(update_interval_ms))
+    }
+
+    pub fn reset(&self) -> Result<(), JsValue> {
+        self.control(Request::Reset)
+    }
+
+    pub fn zoom_in(&self) -> Result<(), JsValue> {
+        self.control(Request::ZoomIn)
+    }
+
+    pub fn zoom_out(&self) -> Result<(), JsValue> {
+        self.control(Request::ZoomOut)
+    }
+
+    pub fn fit_pattern(&self) -> Result<(), JsValue> {
+        self.control(Request::FitPattern)
+    }
+
+    pub fn follow_centroid(&self) -> Result<(), JsValue> {
+        self.control(Request::FollowCentroid)
+    }
+
+    pub fn set_theme(&self, theme: &str) -> Result<(), JsValue> {
+        let theme: Theme = theme.parse().map_err(|message: String| js_error(&message))?;
+        self.control(Request::SetTheme(theme))
+    }
+
+    pub fn set_color_mode(&self, color_mode: &str) -> Result<(), JsValue> {
+        let color_mode: ColorMode = color_mode.parse().map_err(|message: String| js_error(&message))?;
+        self.control(Request::SetColorMode(color_mode))
+    }
+
+    pub fn toggle_hud(&self) -> Result<(), JsValue> {
+        self.control(Request::ToggleHud)
+    }
+
+    // Resizes the canvas and the board with the parent element of the canvas, keeping the live cells. The parent should
+    // not take its size from the canvas, give it a height in CSS for instance.
+    pub fn fit_container(&mut self) -> Result<(), JsValue> {
+        self.running.fit_container().map_err(|message| js_error(&message))
+    }
+
+    // Ends the animation and removes the listeners from the canvas. The last frame stays on the canvas.
+    pub fn stop(&mut self) {
+        self.running.stop();
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn running(&self) -> bool {
+        self.running.is_running()
+    }
+
+    // The generation number, as a JavaScript number rather than a BigInt.
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> f64 {
+        self.running.with_view(|view| view.controller().game_state.generation as f64)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn population(&self) -> usize {
+        self.running.with_view(|view| view.controller().game_state.board.population())
+    }
+
+    fn control(&self, request: Request) -> Result<(), JsValue> {
+        self.running.with_view(|view| {
+            request.apply(view)?;
+            view.render();
+            Ok(())
+        })
+        .map_err(|message: String| js_error(&message))
+    }
+}
+
+// Returns the options of a simulation, sized in pixels as on the command line, once they are checked. The cell size is
+// 10 pixels and the interval that of the page unless given. Also used for the games of workers.
+pub fn simulation_options(width: usize, height: usize, cell_size: Option<usize>, update_interval_ms: Option<u32>) -> Result<Options, String> {
+    let defaults = Options::default();
+    let cell_size = cell_size.unwrap_or(DEFAULT_CELL_SIZE);
+    let pixels = |cells: usize| cells.checked_mul(cell_size).ok_or_else(|| format!("A board of {}x{} cells is too large", width, height));
+    let options = Options {
+        width: pixels(width)?,
+        height: pixels(height)?,
+        cell_size,
+        update_interval_ms: update_interval_ms.map_or(defaults.update_interval_ms, |interval| interval as usize),
+        ..defaults
+    };
+    cli::validate(&options)?;
+    Ok(options)
+}
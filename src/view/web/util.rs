@@ -1,5 +1,58 @@
-use web_sys::window;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::js_sys::{self, Array};
+use web_sys::{CanvasRenderingContext2d, Element, HtmlCanvasElement, Performance, ResizeObserver, ResizeObserverEntry};
 
+// Reads the global `performance`, which windows, workers and Node all have.
 pub fn performance_now() -> f64 {
-    window().unwrap().performance().unwrap().now()
-}
\ No newline at end of file
+    js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+        .ok()
+        .filter(|performance| performance.is_object())
+        .map_or(0.0, |performance| performance.unchecked_into::<Performance>().now())
+}
+
+pub fn context_2d(canvas: &HtmlCanvasElement) -> Result<CanvasRenderingContext2d, String> {
+    canvas.get_context("2d")
+        .map_err(|error| format!("Cannot get the canvas context: {:?}", error))?
+        .ok_or("The canvas has no 2d context")?
+        .dyn_into::<CanvasRenderingContext2d>()
+        .map_err(|_| "The canvas has no 2d context".to_string())
+}
+
+// Converts a position in the page to canvas pixels, through the CSS size and transform of the canvas.
+pub fn canvas_point(canvas: &HtmlCanvasElement, client_x: i32, client_y: i32) -> (f64, f64) {
+    let rect = canvas.get_bounding_client_rect();
+    let scale = |pixels: u32, css_size: f64| if css_size > 0.0 { pixels as f64 / css_size } else { 1.0 };
+    (
+        (client_x as f64 - rect.left()) * scale(canvas.width(), rect.width()),
+        (client_y as f64 - rect.top()) * scale(canvas.height(), rect.height()),
+    )
+}
+
+// Calls back with the size of the content box of an element, in CSS pixels, when observing starts and whenever it
+// changes, until dropped.
+pub struct SizeObserver {
+    observer: ResizeObserver,
+    _callback: Closure<dyn FnMut(Array)>,
+}
+
+impl SizeObserver {
+    pub fn new(element: &Element, mut on_resize: impl FnMut(u32, u32) + 'static) -> Result<SizeObserver, String> {
+        let callback = Closure::wrap(Box::new(move |entries: Array| {
+            if let Some(entry) = entries.iter().last() {
+                let rect = entry.unchecked_into::<ResizeObserverEntry>().content_rect();
+                on_resize(rect.width() as u32, rect.height() as u32);
+            }
+        }) as Box<dyn FnMut(Array)>);
+        let observer = ResizeObserver::new(callback.as_ref().unchecked_ref())
+            .map_err(|error| format!("Cannot observe the size of the container: {:?}", error))?;
+        observer.observe(element);
+        Ok(SizeObserver { observer, _callback: callback })
+    }
+}
+
+impl Drop for SizeObserver {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
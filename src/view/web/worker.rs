@@ -0,0 +1,514 @@
+use game_of_life::{CameraMode, ColorMode, GameState, Theme};
+
+use crate::view::game_view_trait::GameViewTrait;
+use crate::view::web::game_view::GameView;
+use crate::view::web::js::js_error;
+use crate::view::web::simulation::simulation_options;
+use crate::view::web::util::performance_now;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::js_sys::{self, Array, Object, Reflect, Uint8ClampedArray};
+use web_sys::{DedicatedWorkerGlobalScope, MessageEvent};
+
+// The page and the worker exchange plain objects with a `type` field, such as `{type: 'step', generations: 10}`.
+// Both ends are written in Rust: `WorkerSimulation` posts requests, and `worker.js` only loads this module in the
+// worker and calls `run_worker`, which steps the game and posts frames back.
+
+// A message from the page to the worker, or a control applied directly to a game on the page, see `apply`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Request {
+    // Creates the game, before any other request.
+    Start { width: usize, height: usize, cell_size: usize, update_interval_ms: u32 },
+    LoadPattern(String),
+    Randomize(f64),
+    Play,
+    Pause,
+    TogglePause,
+    Step(u32),
+    SetSpeed(u32),
+    Reset,
+    ZoomIn,
+    ZoomOut,
+    // Zooms by `factor` around the (x, y) point of the canvas.
+    Zoom { factor: f64, x: f64, y: f64 },
+    // Fits the board to a canvas of `width` x `height` pixels.
+    Resize { width: u32, height: u32 },
+    // The pointers on the canvas, in canvas pixels, see `Pointers`.
+    PressPointer { id: i32, x: f64, y: f64, button: i16, touch: bool },
+    MovePointer { id: i32, x: f64, y: f64 },
+    ReleasePointer { id: i32 },
+    FitPattern,
+    FollowCentroid,
+    SetTheme(Theme),
+    SetColorMode(ColorMode),
+    ToggleHud,
+}
+
+impl Request {
+    // Applies the request to a started game. The page-level functions of `js.rs` and `Simulation` control their game
+    // with the same requests, applied directly rather than posted to a worker.
+    pub fn apply(self, view: &mut GameView) -> Result<(), String> {
+        match self {
+            Request::Start { .. } => return Err("The game has already been started".to_string()),
+            Request::LoadPattern(text) => view.load_pattern(&text)?,
+            Request::Randomize(probability) => view.randomize(probability),
+            Request::Play => view.play(),
+            Request::Pause => view.pause(),
+            Request::TogglePause => view.toggle_pause(),
+            Request::Step(generations) => view.step_n(generations as u64),
+            Request::SetSpeed(update_interval_ms) => view.set_speed(update_interval_ms as usize),
+            Request::Reset => view.reset(),
+            Request::ZoomIn => view.camera().zoom_in(),
+            Request::ZoomOut => view.camera().zoom_out(),
+            Request::Zoom { factor, x, y } => view.camera().zoom_at(factor, x, y),
+            Request::Resize { width, height } => view.resize(width, height),
+            Request::PressPointer { id, x, y, button, touch } => {
+                view.press_pointer(id, (x, y), button, touch);
+            }
+            Request::MovePointer { id, x, y } => {
+                view.move_pointer(id, (x, y));
+            }
+            Request::ReleasePointer { id } => {
+                view.release_pointer(id);
+            }
+            Request::FitPattern => view.camera().toggle_mode(CameraMode::FitPattern),
+            Request::FollowCentroid => view.camera().toggle_mode(CameraMode::FollowCentroid),
+            Request::SetTheme(theme) => view.controller().theme = theme,
+            Request::SetColorMode(color_mode) => view.controller().set_color_mode(color_mode),
+            Request::ToggleHud => view.hud().toggle(),
+        }
+        Ok(())
+    }
+
+    pub fn to_js(&self) -> JsValue {
+        let message = Message::new(self.kind());
+        match self {
+            Request::Start { width, height, cell_size, update_interval_ms } => message
+                .with("width", *width as f64)
+                .with("height", *height as f64)
+                .with("cell_size", *cell_size as f64)
+                .with("update_interval_ms", *update_interval_ms),
+            Request::LoadPattern(text) => message.with("text", text.as_str()),
+            Request::Randomize(probability) => message.with("probability", *probability),
+            Request::Step(generations) => message.with("generations", *generations),
+            Request::SetSpeed(update_interval_ms) => message.with("update_interval_ms", *update_interval_ms),
+            Request::Zoom { factor, x, y } => message.with("factor", *factor).with("x", *x).with("y", *y),
+            Request::Resize { width, height } => message.with("width", *width).with("height", *height),
+            Request::PressPointer { id, x, y, button, touch } =>
+                message.with("id", *id).with("x", *x).with("y", *y).with("button", *button).with("touch", *touch),
+            Request::MovePointer { id, x, y } => message.with("id", *id).with("x", *x).with("y", *y),
+            Request::ReleasePointer { id } => message.with("id", *id),
+            Request::SetTheme(theme) => message.with("theme", theme.to_string()),
+            Request::SetColorMode(color_mode) => message.with("color_mode", color_mode.to_string()),
+            _ => message,
+        }
+        .into()
+    }
+
+    pub fn from_js(value: &JsValue) -> Result<Request, String> {
+        let message = Message::read(value)?;
+        Ok(match message.kind()?.as_str() {
+            "start" => Request::Start {
+                width: message.number("width")? as usize,
+                height: message.number("height")? as usize,
+                cell_size: message.number("cell_size")? as usize,
+                update_interval_ms: message.number("update_interval_ms")? as u32,
+            },
+            "load_pattern" => Request::LoadPattern(message.string("text")?),
+            "randomize" => Request::Randomize(message.number("probability")?),
+            "play" => Request::Play,
+            "pause" => Request::Pause,
+            "toggle_pause" => Request::TogglePause,
+            "step" => Request::Step(message.number("generations")? as u32),
+            "set_speed" => Request::SetSpeed(message.number("update_interval_ms")? as u32),
+            "reset" => Request::Reset,
+            "zoom_in" => Request::ZoomIn,
+            "zoom_out" => Request::ZoomOut,
+            "zoom" => Request::Zoom { factor: message.number("factor")?, x: message.number("x")?, y: message.number("y")? },
+            "resize" => Request::Resize { width: message.number("width")? as u32, height: message.number("height")? as u32 },
+            "press_pointer" => Request::PressPointer {
+                id: message.number("id")? as i32,
+                x: message.number("x")?,
+                y: message.number("y")?,
+                button: message.number("button")? as i16,
+                touch: message.boolean("touch")?,
+            },
+            "move_pointer" => Request::MovePointer { id: message.number("id")? as i32, x: message.number("x")?, y: message.number("y")? },
+            "release_pointer" => Request::ReleasePointer { id: message.number("id")? as i32 },
+            "fit_pattern" => Request::FitPattern,
+            "follow_centroid" => Request::FollowCentroid,
+            "set_theme" => Request::SetTheme(message.string("theme")?.parse()?),
+            "set_color_mode" => Request::SetColorMode(message.string("color_mode")?.parse()?),
+            "toggle_hud" => Request::ToggleHud,
+            kind => return Err(format!("Unknown request '{}'", kind)),
+        })
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Request::Start { .. } => "start",
+            Request::LoadPattern(_) => "load_pattern",
+            Request::Randomize(_) => "randomize",
+            Request::Play => "play",
+            Request::Pause => "pause",
+            Request::TogglePause => "toggle_pause",
+            Request::Step(_) => "step",
+            Request::SetSpeed(_) => "set_speed",
+            Request::Reset => "reset",
+            Request::ZoomIn => "zoom_in",
+            Request::ZoomOut => "zoom_out",
+            Request::Zoom { .. } => "zoom",
+            Request::Resize { .. } => "resize",
+            Request::PressPointer { .. } => "press_pointer",
+            Request::MovePointer { .. } => "move_pointer",
+            Request::ReleasePointer { .. } => "release_pointer",
+            Request::FitPattern => "fit_pattern",
+            Request::FollowCentroid => "follow_centroid",
+            Request::SetTheme(_) => "set_theme",
+            Request::SetColorMode(_) => "set_color_mode",
+            Request::ToggleHud => "toggle_hud",
+        }
+    }
+}
+
+// A message from the worker to the page.
+#[derive(Clone, Debug)]
+pub enum Reply {
+    // The RGBA pixels of the board, drawn with the HUD.
+    Frame { width: u32, height: u32, pixels: Uint8ClampedArray, generation: f64, population: usize },
+    // A request failed, for instance because its pattern could not be parsed.
+    Error(String),
+}
+
+impl Reply {
+    pub fn to_js(&self) -> JsValue {
+        match self {
+            Reply::Frame { width, height, pixels, generation, population } => Message::new("frame")
+                .with("width", *width)
+                .with("height", *height)
+                .with("pixels", pixels.clone())
+                .with("generation", *generation)
+                .with("population", *population as f64),
+            Reply::Error(message) => Message::new("error").with("message", message.as_str()),
+        }
+        .into()
+    }
+
+    pub fn from_js(value: &JsValue) -> Result<Reply, String> {
+        let message = Message::read(value)?;
+        match message.kind()?.as_str() {
+            "frame" => Ok(Reply::Frame {
+                width: message.number("width")? as u32,
+                height: message.number("height")? as u32,
+                pixels: message.get("pixels").dyn_into().map_err(|_| "The frame has no pixels".to_string())?,
+                generation: message.number("generation")?,
+                population: message.number("population")? as usize,
+            }),
+            "error" => Ok(Reply::Error(message.string("message")?)),
+            kind => Err(format!("Unknown reply '{}'", kind)),
+        }
+    }
+
+    // The buffers moved to the page rather than copied.
+    fn transfer(&self) -> Array {
+        match self {
+            Reply::Frame { pixels, .. } => Array::of1(&pixels.buffer()),
+            Reply::Error(_) => Array::new(),
+        }
+    }
+}
+
+// A message object under construction or being read.
+struct Message(Object);
+
+impl Message {
+    fn new(kind: &str) -> Message {
+        Message(Object::new()).with("type", kind)
+    }
+
+    fn read(value: &JsValue) -> Result<Message, String> {
+        value.dyn_ref::<Object>().map(|object| Message(object.clone())).ok_or_else(|| "The message is not an object".to_string())
+    }
+
+    fn with(self, key: &str, value: impl Into<JsValue>) -> Message {
+        let _ = Reflect::set(&self.0, &JsValue::from_str(key), &value.into());
+        self
+    }
+
+    fn get(&self, key: &str) -> JsValue {
+        Reflect::get(&self.0, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+    }
+
+    fn kind(&self) -> Result<String, String> {
+        self.string("type")
+    }
+
+    fn number(&self, key: &str) -> Result<f64, String> {
+        self.get(key).as_f64().ok_or_else(|| format!("The message has no number '{}'", key))
+    }
+
+    fn boolean(&self, key: &str) -> Result<bool, String> {
+        self.get(key).as_bool().ok_or_else(|| format!("The message has no boolean '{}'", key))
+    }
+
+    fn string(&self, key: &str) -> Result<String, String> {
+        self.get(key).as_string().ok_or_else(|| format!("The message has no string '{}'", key))
+    }
+}
+
+impl From<Message> for JsValue {
+    fn from(message: Message) -> JsValue {
+        message.0.into()
+    }
+}
+
+// The game run by a worker, which exists once the page has sent the Start request.
+#[derive(Default)]
+pub struct WorkerEngine {
+    view: Option<GameView>,
+}
+
+impl WorkerEngine {
+    const IDLE_INTERVAL_MS: u32 = 100;
+
+    pub fn handle(&mut self, request: Request) -> Result<(), String> {
+        if let Request::Start { width, height, cell_size, update_interval_ms } = request {
+            // The messages may come from any script, so the game is checked like that of a `Simulation`.
+            let options = simulation_options(width, height, Some(cell_size), Some(update_interval_ms))?;
+            let game_state = GameState::new(options.width, options.height, options.cell_size);
+            self.view = Some(GameView::new(game_state, options.cell_size, options.update_interval_ms));
+            return Ok(());
+        }
+        let view = self.view.as_mut().ok_or("The game has not been started")?;
+        request.apply(view)
+    }
+
+    // Advances the game when its interval has elapsed at `current_time`, returns whether the board changed.
+    pub fn advance(&mut self, current_time: f64) -> bool {
+        self.view.as_mut().is_some_and(|view| view.advance(current_time))
+    }
+
+    // Draws the board, None until the game has been started.
+    pub fn frame(&mut self) -> Option<Reply> {
+        let view = self.view.as_mut()?;
+        let controller = view.controller();
+        let (generation, population) = (controller.game_state.generation as f64, controller.game_state.board.population());
+        let frame = view.draw();
+        Some(Reply::Frame {
+            width: frame.width as u32,
+            height: frame.height as u32,
+            pixels: Uint8ClampedArray::from(&frame.pixels[..]),
+            generation,
+            population,
+        })
+    }
+
+    pub fn update_interval_ms(&mut self) -> u32 {
+        self.view.as_mut().map_or(Self::IDLE_INTERVAL_MS, |view| view.controller().update_interval().as_millis() as u32)
+    }
+}
+
+// Whether this module runs in a worker rather than in a page.
+pub fn in_worker() -> bool {
+    js_sys::global().is_instance_of::<DedicatedWorkerGlobalScope>()
+}
+
+type Tick = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
+// Runs the game of a `WorkerSimulation` in the worker calling it. `pending` holds the messages received while the module
+// was loading, which are handled first.
+#[wasm_bindgen]
+pub fn run_worker(pending: Array) -> Result<(), JsValue> {
+    let scope: DedicatedWorkerGlobalScope = js_sys::global()
+        .dyn_into()
+        .map_err(|_| js_error("run_worker must be called in a dedicated worker"))?;
+    let engine = Rc::new(RefCell::new(WorkerEngine::default()));
+    for message in pending.iter() {
+        receive(&mut engine.borrow_mut(), &scope, &message);
+    }
+
+    let on_message = {
+        let (engine, scope) = (engine.clone(), scope.clone());
+        Closure::wrap(Box::new(move |event: MessageEvent| {
+            receive(&mut engine.borrow_mut(), &scope, &event.data());
+        }) as Box<dyn FnMut(MessageEvent)>)
+    };
+    scope.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    // The callbacks live as long as the worker, which the page terminates when it is done with it.
+    on_message.forget();
+    schedule_ticks(engine, scope);
+    Ok(())
+}
+
+fn receive(engine: &mut WorkerEngine, scope: &DedicatedWorkerGlobalScope, message: &JsValue) {
+    let reply = match Request::from_js(message).and_then(|request| engine.handle(request)) {
+        Ok(()) => engine.frame(),
+        Err(message) => Some(Reply::Error(message)),
+    };
+    if let Some(reply) = reply {
+        post(scope, &reply);
+    }
+}
+
+fn post(scope: &DedicatedWorkerGlobalScope, reply: &Reply) {
+    let _ = scope.post_message_with_transfer(&reply.to_js(), &reply.transfer());
+}
+
+// Advances the game at its own speed with a chain of timeouts, posting a frame whenever the board changes.
+fn schedule_ticks(engine: Rc<RefCell<WorkerEngine>>, scope: DedicatedWorkerGlobalScope) {
+    let tick: Tick = Rc::new(RefCell::new(None));
+    let next_tick = tick.clone();
+    let timer_scope = scope.clone();
+    *tick.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+        let mut engine = engine.borrow_mut();
+        if engine.advance(performance_now()) {
+            if let Some(reply) = engine.frame() {
+                post(&scope, &reply);
+            }
+        }
+        set_timeout(&scope, &next_tick, engine.update_interval_ms());
+    }) as Box<dyn FnMut()>));
+    set_timeout(&timer_scope, &tick, 0);
+}
+
+fn set_timeout(scope: &DedicatedWorkerGlobalScope, tick: &Tick, delay_ms: u32) {
+    if let Some(callback) = tick.borrow().as_ref() {
+        let _ = scope.set_timeout_with_callback_and_timeout_and_arguments_0(callback.as_ref().unchecked_ref(), delay_ms as i32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    const GLIDER: &str = "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!";
+
+    fn started_engine() -> WorkerEngine {
+        let mut engine = WorkerEngine::default();
+        engine.handle(Request::Start { width: 16, height: 12, cell_size: 2, update_interval_ms: 100 }).unwrap();
+        engine
+    }
+
+    #[wasm_bindgen_test]
+    fn requests_go_through_javascript_unchanged() {
+        let requests = [
+            Request::Start { width: 40, height: 30, cell_size: 4, update_interval_ms: 50 },
+            Request::LoadPattern(GLIDER.to_string()),
+            Request::Randomize(0.25),
+            Request::Step(10),
+            Request::Zoom { factor: 1.25, x: 3.0, y: 4.5 },
+            Request::PressPointer { id: 3, x: 10.5, y: 4.0, button: 0, touch: true },
+            Request::MovePointer { id: 3, x: 12.0, y: 4.0 },
+            Request::ReleasePointer { id: 3 },
+            Request::Resize { width: 640, height: 360 },
+            Request::SetTheme(Theme::Dark),
+            Request::SetColorMode(ColorMode::Age),
+            Request::ToggleHud,
+        ];
+        for request in requests {
+            assert_eq!(Request::from_js(&request.to_js()), Ok(request));
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn malformed_requests_are_rejected() {
+        assert!(Request::from_js(&JsValue::from_str("play")).is_err());
+        assert_eq!(Request::from_js(&Message::new("jump").into()), Err("Unknown request 'jump'".to_string()));
+        assert!(Request::from_js(&Message::new("step").into()).is_err());
+        assert!(Request::from_js(&Message::new("set_theme").with("theme", "neon").into()).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn invalid_games_are_not_started() {
+        let mut engine = WorkerEngine::default();
+        for request in [
+            Request::Start { width: 0, height: 12, cell_size: 2, update_interval_ms: 100 },
+            Request::Start { width: 65536, height: 65536, cell_size: 1, update_interval_ms: 100 },
+            Request::Start { width: 16, height: 12, cell_size: 0, update_interval_ms: 100 },
+            Request::Start { width: 16, height: 12, cell_size: usize::MAX, update_interval_ms: 100 },
+            Request::Start { width: 16, height: 12, cell_size: 2, update_interval_ms: 0 },
+        ] {
+            assert!(engine.handle(request.clone()).is_err(), "{:?} was accepted", request);
+        }
+        assert!(engine.frame().is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn requests_need_a_started_game() {
+        let mut engine = WorkerEngine::default();
+        assert!(engine.handle(Request::Play).is_err());
+        assert!(engine.frame().is_none());
+        assert!(!engine.advance(1000.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn requests_apply_to_a_view_on_the_page() {
+        let mut view = GameView::new(GameState::new(16, 12, 1), 2, 100);
+        Request::LoadPattern(GLIDER.to_string()).apply(&mut view).unwrap();
+        Request::Step(4).apply(&mut view).unwrap();
+        assert_eq!(view.controller().game_state.generation, 4);
+        assert!(Request::Start { width: 16, height: 12, cell_size: 2, update_interval_ms: 100 }.apply(&mut view).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn the_engine_steps_and_replies_with_frames() {
+        let mut engine = started_engine();
+        engine.handle(Request::LoadPattern(GLIDER.to_string())).unwrap();
+        engine.handle(Request::Step(4)).unwrap();
+
+        let reply = Reply::from_js(&engine.frame().unwrap().to_js()).unwrap();
+        let Reply::Frame { width, height, pixels, generation, population } = reply else {
+            panic!("Expected a frame, got {:?}", reply);
+        };
+        assert_eq!((width, height), (32, 24));
+        assert_eq!(pixels.length(), 32 * 24 * 4);
+        assert_eq!((generation, population), (4.0, 5));
+    }
+
+    #[wasm_bindgen_test]
+    fn resizing_keeps_the_live_cells() {
+        let mut engine = started_engine();
+        engine.handle(Request::LoadPattern(GLIDER.to_string())).unwrap();
+        engine.handle(Request::Resize { width: 61, height: 20 }).unwrap();
+        let Some(Reply::Frame { width, height, population, .. }) = engine.frame() else {
+            panic!("Expected a frame");
+        };
+        assert_eq!((width, height, population), (61, 20, 5));
+    }
+
+    #[wasm_bindgen_test]
+    fn pointers_edit_the_board() {
+        let mut engine = started_engine();
+        engine.handle(Request::PressPointer { id: 1, x: 3.0, y: 3.0, button: 0, touch: false }).unwrap();
+        engine.handle(Request::MovePointer { id: 1, x: 9.0, y: 3.0 }).unwrap();
+        engine.handle(Request::ReleasePointer { id: 1 }).unwrap();
+        let Some(Reply::Frame { population, .. }) = engine.frame() else {
+            panic!("Expected a frame");
+        };
+        assert_eq!(population, 4);
+    }
+
+    #[wasm_bindgen_test]
+    fn patterns_that_do_not_fit_are_reported() {
+        let mut engine = started_engine();
+        assert!(engine.handle(Request::LoadPattern("x = 40, y = 1\n40o!".to_string())).is_err());
+        let reply = Reply::from_js(&Reply::Error("Cannot parse".to_string()).to_js()).unwrap();
+        assert!(matches!(reply, Reply::Error(message) if message == "Cannot parse"));
+    }
+
+    #[wasm_bindgen_test]
+    fn the_game_advances_at_its_own_speed() {
+        let mut engine = started_engine();
+        engine.handle(Request::Randomize(0.5)).unwrap();
+        engine.handle(Request::Play).unwrap();
+        assert!(!engine.advance(50.0));
+        assert!(engine.advance(100.0));
+        assert!(!engine.advance(150.0));
+        assert_eq!(engine.update_interval_ms(), 100);
+    }
+}
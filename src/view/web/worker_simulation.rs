@@ -0,0 +1,253 @@
+use game_of_life::{parse_pattern, ColorMode, Theme};
+
+use crate::view::web::game_view::{GameView, Listener};
+use crate::view::web::js::{console_log, js_error};
+use crate::view::web::simulation::simulation_options;
+use crate::view::web::util::{canvas_point, context_2d, SizeObserver};
+use crate::view::web::worker::{Reply, Request};
+
+use std::cell::Cell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Event, HtmlCanvasElement, ImageData, MessageEvent, PointerEvent, WheelEvent, Worker, WorkerOptions, WorkerType};
+
+// A game stepped in a Web Worker, so that large boards do not freeze the page:
+//
+//     const simulation = new WorkerSimulation(canvas, './worker.js', 400, 300, 2);
+//     simulation.load_pattern(rle);
+//     simulation.play();
+//
+// The worker script loads this module and hands its messages to `run_worker`, see `gh-pages/worker.js`. The methods
+// post requests and return at once, the worker posts a frame back whenever the board changes and it is drawn on the
+// canvas. Errors of the worker, such as a pattern that does not fit, are logged to the console.
+#[wasm_bindgen]
+pub struct WorkerSimulation {
+    worker: Worker,
+    canvas: HtmlCanvasElement,
+    // The generation and population of the last frame received.
+    latest: Rc<Cell<(f64, usize)>>,
+    on_message: Option<Closure<dyn FnMut(MessageEvent)>>,
+    listeners: Vec<Listener>,
+    size_observer: Option<SizeObserver>,
+}
+
+#[wasm_bindgen]
+impl WorkerSimulation {
+    // Starts an empty board of `width` x `height` cells, each `cell_size` pixels wide (10 by default), in a worker
+    // running the module script at `script_url`. The sizes are checked like those of a `Simulation`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas: HtmlCanvasElement, script_url: &str, width: usize, height: usize, cell_size: Option<usize>)
+        -> Result<WorkerSimulation, JsValue> {
+        let options = simulation_options(width, height, cell_size, None).map_err(|message| js_error(&message))?;
+        // Checked boards are at most a few million pixels wide.
+        canvas.set_width(options.width as u32);
+        canvas.set_height(options.height as u32);
+        let context = context_2d(&canvas).map_err(|message| js_error(&message))?;
+        let _ = canvas.style().set_property("touch-action", "none");
+
+        let worker_options = WorkerOptions::new();
+        worker_options.set_type(WorkerType::Module);
+        let worker = Worker::new_with_options(script_url, &worker_options)?;
+
+        let latest = Rc::new(Cell::new((0.0, 0)));
+        let on_message = {
+            let (canvas, latest) = (canvas.clone(), latest.clone());
+            Closure::wrap(Box::new(move |event: MessageEvent| match Reply::from_js(&event.data()) {
+                Ok(Reply::Frame { width, height, pixels, generation, population }) => {
+                    latest.set((generation, population));
+                    if (canvas.width(), canvas.height()) != (width, height) {
+                        canvas.set_width(width);
+                        canvas.set_height(height);
+                    }
+                    match ImageData::new_with_js_u8_clamped_array_and_sh(&pixels, width, height) {
+                        Ok(image_data) => {
+                            let _ = context.put_image_data(&image_data, 0.0, 0.0);
+                        }
+                        Err(error) => console_log(&format!("Cannot draw the board: {:?}", error)),
+                    }
+                }
+                Ok(Reply::Error(message)) => console_log(&format!("Worker error: {}", message)),
+                Err(message) => console_log(&format!("Unexpected message from the worker: {}", message)),
+            }) as Box<dyn FnMut(MessageEvent)>)
+        };
+        worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        let mut simulation = WorkerSimulation { worker, canvas, latest, on_message: Some(on_message), listeners: Vec::new(), size_observer: None };
+        let (cell_size, update_interval_ms) = (options.cell_size, options.update_interval_ms as u32);
+        simulation.post(Request::Start { width, height, cell_size, update_interval_ms });
+        simulation.add_pointer_listeners();
+        Ok(simulation)
+    }
+
+    // Replaces the board with a plaintext or RLE pattern, centred, which `reset()` then goes back to.
+    // The pattern is parsed here too, so that syntax errors are thrown rather than logged by the worker.
+    pub fn load_pattern(&self, text: &str) -> Result<(), JsValue> {
+        parse_pattern(text).map_err(|error| js_error(&error.to_string()))?;
+        self.post(Request::LoadPattern(text.to_string()));
+        Ok(())
+    }
+
+    // Fills the board randomly, each cell being alive with the given probability.
+    pub fn randomize(&self, probability: f64) {
+        self.post(Request::Randomize(probability));
+    }
+
+    pub fn play(&self) {
+        self.post(Request::Play);
+    }
+
+    pub fn pause(&self) {
+        self.post(Request::Pause);
+    }
+
+    pub fn toggle_pause(&self) {
+        self.post(Request::TogglePause);
+    }
+
+    // Advances by one generation unless `generations` is given.
+    pub fn step(&self, generations: Option<u32>) {
+        self.post(Request::Step(generations.unwrap_or(1)));
+    }
+
+    pub fn set_speed(&self, update_interval_ms: u32) {
+        self.post(Request::SetSpeed(update_interval_ms));
+    }
+
+    pub fn reset(&self) {
+        self.post(Request::Reset);
+    }
+
+    pub fn zoom_in(&self) {
+        self.post(Request::ZoomIn);
+    }
+
+    pub fn zoom_out(&self) {
+        self.post(Request::ZoomOut);
+    }
+
+    pub fn fit_pattern(&self) {
+        self.post(Request::FitPattern);
+    }
+
+    pub fn follow_centroid(&self) {
+        self.post(Request::FollowCentroid);
+    }
+
+    pub fn set_theme(&self, theme: &str) -> Result<(), JsValue> {
+        let theme: Theme = theme.parse().map_err(|message: String| js_error(&message))?;
+        self.post(Request::SetTheme(theme));
+        Ok(())
+    }
+
+    pub fn set_color_mode(&self, color_mode: &str) -> Result<(), JsValue> {
+        let color_mode: ColorMode = color_mode.parse().map_err(|message: String| js_error(&message))?;
+        self.post(Request::SetColorMode(color_mode));
+        Ok(())
+    }
+
+    pub fn toggle_hud(&self) {
+        self.post(Request::ToggleHud);
+    }
+
+    // Resizes the board with the parent element of the canvas, keeping the live cells. The canvas takes the size of the
+    // frames the worker posts back. The parent should not take its size from the canvas.
+    pub fn fit_container(&mut self) -> Result<(), JsValue> {
+        let container = self.canvas.parent_element().ok_or_else(|| js_error("The canvas has no parent element"))?;
+        let worker = self.worker.clone();
+        let observer = SizeObserver::new(&container, move |width, height| {
+            if width > 0 && height > 0 {
+                let _ = worker.post_message(&Request::Resize { width, height }.to_js());
+            }
+        });
+        self.size_observer = Some(observer.map_err(|message| js_error(&message))?);
+        Ok(())
+    }
+
+    // Terminates the worker and removes the listeners from the canvas. The last frame stays on the canvas.
+    pub fn stop(&mut self) {
+        if self.on_message.take().is_some() {
+            self.worker.set_onmessage(None);
+            self.worker.terminate();
+        }
+        self.size_observer = None;
+        for (event, listener) in self.listeners.drain(..) {
+            let _ = self.canvas.remove_event_listener_with_callback(event, listener.as_ref().unchecked_ref());
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn running(&self) -> bool {
+        self.on_message.is_some()
+    }
+
+    // The generation of the last frame drawn, which may lag behind the worker.
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> f64 {
+        self.latest.get().0
+    }
+
+    // The population of the last frame drawn.
+    #[wasm_bindgen(getter)]
+    pub fn population(&self) -> usize {
+        self.latest.get().1
+    }
+
+    fn post(&self, request: Request) {
+        if self.running() {
+            let _ = self.worker.post_message(&request.to_js());
+        }
+    }
+
+    // The camera and the editor live in the worker, so the wheel and the pointers are forwarded to it.
+    fn add_pointer_listeners(&mut self) {
+        self.add_listener("wheel", |canvas, event| {
+            let event: WheelEvent = event.unchecked_into();
+            let factor = GameView::wheel_zoom(event.delta_y())?;
+            event.prevent_default();
+            let (x, y) = canvas_point(canvas, event.client_x(), event.client_y());
+            Some(Request::Zoom { factor, x, y })
+        });
+        self.add_listener("pointerdown", |canvas, event| {
+            let event: PointerEvent = event.unchecked_into();
+            event.prevent_default();
+            let _ = canvas.set_pointer_capture(event.pointer_id());
+            let (x, y) = canvas_point(canvas, event.client_x(), event.client_y());
+            let touch = event.pointer_type() == "touch";
+            Some(Request::PressPointer { id: event.pointer_id(), x, y, button: event.button(), touch })
+        });
+        // Moves without any button held are not sent, since they do nothing.
+        self.add_listener("pointermove", |canvas, event| {
+            let event: PointerEvent = event.unchecked_into();
+            let (x, y) = canvas_point(canvas, event.client_x(), event.client_y());
+            (event.buttons() != 0).then(|| Request::MovePointer { id: event.pointer_id(), x, y })
+        });
+        for name in ["pointerup", "pointercancel"] {
+            self.add_listener(name, |_, event| {
+                Some(Request::ReleasePointer { id: event.unchecked_into::<PointerEvent>().pointer_id() })
+            });
+        }
+        self.add_listener("contextmenu", |_, event| {
+            event.prevent_default();
+            None
+        });
+    }
+
+    fn add_listener(&mut self, event: &'static str, to_request: impl Fn(&HtmlCanvasElement, Event) -> Option<Request> + 'static) {
+        let (worker, canvas) = (self.worker.clone(), self.canvas.clone());
+        let listener = Closure::wrap(Box::new(move |event: Event| {
+            if let Some(request) = to_request(&canvas, event) {
+                let _ = worker.post_message(&request.to_js());
+            }
+        }) as Box<dyn FnMut(Event)>);
+        let _ = self.canvas.add_event_listener_with_callback(event, listener.as_ref().unchecked_ref());
+        self.listeners.push((event, listener));
+    }
+}
+
+impl Drop for WorkerSimulation {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}